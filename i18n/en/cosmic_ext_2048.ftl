@@ -2,3 +2,4 @@ app-title = 2048 for COSMIC
 about = About
 view = View
 welcome = Welcome to COSMIC! ✨
+high-scores = High scores
@@ -2,3 +2,4 @@ app-title = 2048 för COSMIC
 about = Om
 view = Visa
 welcome = Välkommen till COSMIC! ✨
+high-scores = Topplista
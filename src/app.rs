@@ -1,250 +1,227 @@
 // SPDX-License-Identifier: GPL-3.0-only
 use cosmic::widget::list::container;
-use rand::seq::IteratorRandom;
-use rand::{seq::SliceRandom, thread_rng};
+use cosmic_ext_2048::engine::ai;
+use cosmic_ext_2048::engine::board::{
+    pair_to_index, Board, Direction, Grid, Moved, Movement, Tile,
+};
+use cosmic_ext_2048::engine::daily::{self, DailyResult, DailyResults};
+use cosmic_ext_2048::engine::evil::Malice;
+use cosmic_ext_2048::engine::merge::Merge;
+use cosmic_ext_2048::engine::puzzle::{Puzzle, SolvedPuzzles};
+use cosmic_ext_2048::engine::record::{GameRecord, RecordedMove};
+use cosmic_ext_2048::engine::save::{SavedGame, MAX_SIDE};
+use cosmic_ext_2048::engine::scores::{HighScore, HighScores};
+use cosmic_ext_2048::engine::shape::Shape;
+use cosmic_ext_2048::engine::spawn::SpawnRules;
+use cosmic_ext_2048::export;
+use cosmic_ext_2048::export::animation::{self, AnimationOptions};
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time;
 
 use crate::fl;
 use cosmic::app::{Command, Core};
 use cosmic::iced::alignment::{Horizontal, Vertical};
-use cosmic::iced::{event, keyboard, Alignment, Event, Length, Subscription};
-use cosmic::widget::{self, button, menu, text, text_input, Grid, Row, Text};
+use cosmic::iced::widget::Space;
+use cosmic::iced::{event, keyboard, window, Alignment, Event, Length, Subscription};
+use cosmic::widget::{self, button, menu, text, text_input, Row, Text};
 use cosmic::{cosmic_theme, theme, Application, ApplicationExt, Element, Renderer, Theme};
+mod editor;
+mod lan;
+mod puzzles;
+mod race;
+mod replay;
 mod widget_colors;
 
 const REPOSITORY: &str = "https://github.com/Kartonrealista/cosmic-ext-2024";
+const HINT_BUDGET: time::Duration = time::Duration::from_millis(300);
+const AUTOPLAY_BUDGET: time::Duration = time::Duration::from_millis(100);
+/// Lengths of a time-attack game offered in the menu, in minutes.
+const TIME_ATTACK_MINUTES: [u64; 3] = [1, 3, 5];
 
 struct Game {
     menu: Menu,
     board: Board,
     old_board: Board,
+    score: usize,
+    old_score: usize,
+    spawn: SpawnRules,
+    /// Deals the spawned tiles, so that the game can be replayed from its seed.
+    rng: StdRng,
+    old_rng: StdRng,
+    /// Moves so far. Games resumed from a save file have no record.
+    record: Option<GameRecord>,
+    /// Whether the game is over and its score has been handed in.
+    finished: bool,
+    /// Whose turn it is in a hot-seat game, `None` when playing alone.
+    hot_seat: Option<Turn>,
+    /// The countdown of a time-attack game.
+    clock: Option<Countdown>,
+    puzzle: Option<Puzzle>,
+    /// Moves made on the puzzle so far.
+    puzzle_moves: usize,
+    /// The day of a daily game, `None` for other games.
+    daily: Option<u64>,
+    /// Whether this is the day's first attempt, the only one that counts.
+    daily_scored: bool,
+    hint: Option<Direction>,
+    hint_pending: bool,
+    hints_used: usize,
+    autoplay: bool,
+    autoplay_pending: bool,
+    /// Pause between two autoplay moves, in milliseconds.
+    autoplay_delay: u32,
+    /// Outcome of the last save or load, shown under the board.
+    status: String,
 }
 impl Game {
     fn new() -> Game {
+        let (spawn_preset_names, spawn_presets) = SpawnRules::presets().into_iter().unzip();
         Game {
-            board: Board::new(4, 4),
-            old_board: Board::new(4, 4),
+            board: Board::new(4, 4, &mut thread_rng()),
+            old_board: Board::new(4, 4, &mut thread_rng()),
+            score: 0,
+            old_score: 0,
+            spawn: SpawnRules::classic(),
+            rng: StdRng::from_entropy(),
+            old_rng: StdRng::from_entropy(),
+            record: None,
+            finished: false,
+            hot_seat: None,
+            clock: None,
+            puzzle: None,
+            puzzle_moves: 0,
+            daily: None,
+            daily_scored: false,
             menu: Menu {
                 width_inptut: String::from("4"),
                 height_inptut: String::from("4"),
                 width: 4,
                 height: 4,
+                spawn_preset_names,
+                spawn_presets,
+                spawn_preset: Some(0),
+                spawn_input: SpawnRules::classic().to_string(),
+                opponent_names: std::iter::once("Random")
+                    .chain(Malice::ALL.iter().map(|malice| malice.label()))
+                    .collect(),
+                shape_names: Shape::ALL.iter().map(|shape| shape.label()).collect(),
+                clock_names: vec!["No clock", "1 minute", "3 minutes", "5 minutes"],
+                clock: 0,
                 start_pressed: false,
             },
+            hint: None,
+            hint_pending: false,
+            hints_used: 0,
+            autoplay: false,
+            autoplay_pending: false,
+            autoplay_delay: 250,
+            status: String::new(),
+        }
+    }
+
+    /// Starts over on `board`, forgetting the score, the hints and the outcome
+    /// of the game before. Every way of dealing or loading a game goes through here.
+    fn begin(&mut self, board: Board, score: usize) {
+        self.board = board;
+        self.old_board = self.board.clone();
+        self.score = score;
+        self.old_score = score;
+        self.finished = false;
+        self.hint = None;
+        self.hints_used = 0;
+    }
+
+    /// The entry this game makes in the high scores of `mode`.
+    fn high_score(&self, mode: String) -> HighScore {
+        HighScore {
+            score: self.score,
+            max_tile: self.board.max_tile(),
+            width: self.menu.width,
+            height: self.menu.height,
+            hints: self.hints_used,
+            mode,
         }
     }
 }
-fn pair_to_index(i: usize, j: usize, width: usize) -> usize {
-    i * width + j
+/// The two halves of a hot-seat round: player one slides the tiles with the
+/// keyboard, then player two clicks the cell the next tile goes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Turn {
+    Slide,
+    /// Placing a tile of this value, drawn by the spawn rules.
+    Place(usize),
 }
-
 struct Menu {
     width_inptut: String,
     height_inptut: String,
     width: usize,
     height: usize,
+    spawn_preset_names: Vec<&'static str>,
+    spawn_presets: Vec<SpawnRules>,
+    /// The preset picked, until the rules are edited by hand.
+    spawn_preset: Option<usize>,
+    spawn_input: String,
+    /// "Random" followed by the labels of every `Malice`.
+    opponent_names: Vec<&'static str>,
+    /// The labels of every `Shape`.
+    shape_names: Vec<&'static str>,
+    /// "No clock" followed by every entry of `TIME_ATTACK_MINUTES`.
+    clock_names: Vec<&'static str>,
+    clock: usize,
     start_pressed: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Board(Vec<Tile>);
-impl Board {
-    const TWO_OR_FOUR: [usize; 10] = [2, 2, 2, 2, 2, 2, 2, 2, 2, 4];
-    fn new(height: usize, width: usize) -> Board {
-        let mut out: Vec<Tile> = (0..(width * height))
-            .map(|id| Tile {
-                tilecontent: None,
-                id,
-            })
-            .collect();
-        let mut ids: Vec<usize> = (0..(width * height)).collect();
-        rand::seq::SliceRandom::shuffle(ids.as_mut_slice(), &mut thread_rng());
-        ids.iter().take(2).for_each(|&id| {
-            out[id].tilecontent = Some(*Self::TWO_OR_FOUR.choose(&mut thread_rng()).unwrap());
-        });
-        Board(out)
-    }
-    fn move_tile_content(&mut self, direction: &keyboard::Key, height: usize, width: usize) {
-        let old_board = self.clone();
-        let mut previous = Tile {
-            tilecontent: None,
-            id: 0,
-        };
-        match direction {
-            keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
-                self.collapse_left(height, width);
-                (0..height).for_each(|h| {
-                    (0..width).for_each(|w| {
-                        self.merge_neighbouring(h, w, &mut previous, width, height, &direction);
-                    });
-                });
-                self.collapse_left(height, width);
-            }
-            keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
-                self.collapse_right(height, width);
-                (0..height).for_each(|h| {
-                    (0..width).rev().for_each(|w| {
-                        self.merge_neighbouring(h, w, &mut previous, width, height, &direction);
-                    });
-                });
-                self.collapse_right(height, width);
-            }
-            keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
-                self.collapse_up(height, width);
-                (0..width).for_each(|w| {
-                    (0..height).for_each(|h| {
-                        self.merge_neighbouring(h, w, &mut previous, width, height, &direction);
-                    });
-                });
-                self.collapse_up(height, width);
-            }
-            keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
-                self.collapse_down(height, width);
-                (0..width).for_each(|w| {
-                    (0..height).rev().for_each(|h| {
-                        self.merge_neighbouring(h, w, &mut previous, width, height, &direction);
-                    });
-                });
-                self.collapse_down(height, width);
-            }
-            _ => {}
-        }
-        std::thread::sleep(time::Duration::from_secs_f64(0.05));
-        if old_board != *self {
-            let empty_ids = self
-                .0
-                .iter()
-                .filter(|&&tile| tile.tilecontent.is_none())
-                .map(|&tile| tile.id);
-            let chosen_id = empty_ids.choose(&mut thread_rng()).unwrap();
-            self.0[chosen_id].tilecontent =
-                Some(*Self::TWO_OR_FOUR.choose(&mut thread_rng()).unwrap());
+/// The clock of a time-attack game, which only runs while the window has focus.
+struct Countdown {
+    length: time::Duration,
+    remaining: time::Duration,
+    /// When the clock was last wound down, `None` while it is paused.
+    last_tick: Option<time::Instant>,
+}
+impl Countdown {
+    fn new(length: time::Duration) -> Countdown {
+        Countdown {
+            length,
+            remaining: length,
+            last_tick: Some(time::Instant::now()),
         }
     }
-    fn collapse_left(&mut self, height: usize, width: usize) {
-        (0..height).for_each(|h| {
-            let mut row = vec![];
-            (0..width).for_each(|w| row.push(self.0[pair_to_index(h, w, width)]));
-            let collapsed = row.iter().filter(|&tile| tile.tilecontent.is_some());
-            if collapsed.clone().copied().collect::<Vec<Tile>>() == row {
-                return;
-            }
-            collapsed
-                .clone()
-                .copied()
-                .enumerate()
-                .for_each(|(w, tile)| {
-                    self.0[pair_to_index(h, w, width)].tilecontent = tile.tilecontent;
-                });
-            (collapsed.count()..width).for_each(|w| {
-                self.0[pair_to_index(h, w, width)].tilecontent = None;
-            });
-        });
-    }
-    fn collapse_right(&mut self, height: usize, width: usize) {
-        (0..height).for_each(|h| {
-            let mut row = vec![];
-            (0..width)
-                .rev()
-                .for_each(|w| row.push(self.0[pair_to_index(h, w, width)]));
-            let collapsed = row.iter().filter(|&tile| tile.tilecontent.is_some());
-            if collapsed.clone().copied().collect::<Vec<Tile>>() == row {
-                return;
-            }
-            collapsed
-                .clone()
-                .copied()
-                .enumerate()
-                .for_each(|(w, tile)| {
-                    self.0[pair_to_index(h, width - w - 1, width)].tilecontent = tile.tilecontent;
-                });
-            (collapsed.count()..width).for_each(|w| {
-                self.0[pair_to_index(h, width - w - 1, width)].tilecontent = None;
-            });
-        });
-    }
-    fn collapse_up(&mut self, height: usize, width: usize) {
-        (0..width).for_each(|w| {
-            let mut column = vec![];
-            (0..height).for_each(|h| column.push(self.0[pair_to_index(h, w, width)]));
-            let collapsed = column.iter().filter(|&tile| tile.tilecontent.is_some());
-            if collapsed.clone().copied().collect::<Vec<Tile>>() == column {
-                return;
-            }
-            collapsed.clone().enumerate().for_each(|(h, tile)| {
-                self.0[pair_to_index(h, w, width)].tilecontent = tile.tilecontent;
-            });
-            (collapsed.count()..height).for_each(|h| {
-                self.0[pair_to_index(h, w, width)].tilecontent = None;
-            });
-        });
-    }
-    fn collapse_down(&mut self, height: usize, width: usize) {
-        (0..width).for_each(|w| {
-            let mut column = vec![];
-            (0..height)
-                .rev()
-                .for_each(|h| column.push(self.0[pair_to_index(h, w, width)]));
-            let collapsed = column.iter().filter(|&tile| tile.tilecontent.is_some());
-            if collapsed.clone().copied().collect::<Vec<Tile>>() == column {
-                return;
-            }
-            collapsed.clone().enumerate().for_each(|(h, tile)| {
-                self.0[pair_to_index(height - h - 1, w, width)].tilecontent = tile.tilecontent;
-            });
-            (collapsed.count()..height).for_each(|h| {
-                self.0[pair_to_index(height - h - 1, w, width)].tilecontent = None;
-            });
-        });
+
+    /// Winds the clock down, returning whether the time is up.
+    fn tick(&mut self) -> bool {
+        let now = time::Instant::now();
+        if let Some(last_tick) = self.last_tick {
+            self.remaining = self.remaining.saturating_sub(now - last_tick);
+            self.last_tick = Some(now);
+        }
+        self.is_up()
     }
-    fn merge_neighbouring(
-        &mut self,
-        h: usize,
-        w: usize,
-        previous: &mut Tile,
-        width: usize,
-        height: usize,
-        direction: &keyboard::Key,
-    ) {
-        match (h, w) {
-            (_, 0) if direction == &keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
-                *previous = self.0[pair_to_index(h, w, width)];
-            }
-            (_, w)
-                if direction == &keyboard::Key::Named(keyboard::key::Named::ArrowRight)
-                    && w == width - 1 =>
-            {
-                *previous = self.0[pair_to_index(h, w, width)];
-            }
-            (0, _) if direction == &keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
-                *previous = self.0[pair_to_index(h, w, width)];
-            }
-            (h, _)
-                if direction == &keyboard::Key::Named(keyboard::key::Named::ArrowDown)
-                    && h == height - 1 =>
-            {
-                *previous = self.0[pair_to_index(h, w, width)];
-            }
-            _ => {
-                let mut current = self.0[pair_to_index(h, w, width)];
-                if previous.tilecontent == current.tilecontent {
-                    if let Some(content) = previous.tilecontent {
-                        self.0[previous.id].tilecontent = Some(content * 2);
-                        self.0[current.id].tilecontent = None;
-                        current.tilecontent = None;
-                    }
-                }
-                *previous = current;
-            }
+
+    fn pause(&mut self, paused: bool) {
+        if paused {
+            self.tick();
+            self.last_tick = None;
+        } else if self.last_tick.is_none() {
+            self.last_tick = Some(time::Instant::now());
         }
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct Tile {
-    tilecontent: Option<usize>,
-    id: usize,
+    fn is_up(&self) -> bool {
+        self.remaining.is_zero()
+    }
+
+    fn view(&self) -> String {
+        let seconds = self.remaining.as_secs();
+        let paused = if self.last_tick.is_none() {
+            " (paused)"
+        } else {
+            ""
+        };
+        format!("{}:{:02}{paused}", seconds / 60, seconds % 60)
+    }
 }
 
 /// This is the struct that represents your application.
@@ -257,6 +234,20 @@ pub struct App2048 {
     /// Key bindings for the application's menu bar.
     key_binds: HashMap<menu::KeyBind, MenuAction>,
     game: Game,
+    high_scores: HighScores,
+    puzzles: Vec<Puzzle>,
+    solved: SolvedPuzzles,
+    daily_results: DailyResults,
+    /// Whether the puzzle selection is shown instead of the menu.
+    picking_puzzle: bool,
+    /// The replay viewer, while it is open.
+    replay: Option<replay::Replay>,
+    /// The board editor, while it is open.
+    editor: Option<editor::Editor>,
+    /// The split-screen race, while it is open.
+    race: Option<race::Race>,
+    /// The network race screen, while it is open.
+    lan: Option<lan::Lan>,
 }
 
 /// This is the enum that contains all the possible variants that your application will need to transmit messages.
@@ -269,9 +260,77 @@ pub enum Message {
     Reset,
     InputWidth(String),
     InputHeight(String),
+    SpawnPreset(usize),
+    Opponent(usize),
+    Shape(usize),
+    InputSpawn(String),
+    ClockLength(usize),
+    ClockTick,
+    StartDaily,
+    ShareDaily,
+    OpenPuzzles,
+    ClosePuzzles,
+    PlayPuzzle(usize),
     StartPressed,
+    StartHotSeat,
+    Place(usize),
     GotoMenu,
     Back,
+    Hint,
+    HintReady(Board, Option<Direction>),
+    ToggleAutoplay,
+    AutoplayDelay(u32),
+    AutoplayMove(Board, Option<Direction>),
+    Save,
+    Load,
+    CopyBoard,
+    CopyEmoji,
+    PasteBoard,
+    Pasted(Option<String>),
+    SaveRecord,
+    OpenReplay,
+    CloseReplay,
+    ReplayPath(String),
+    LoadReplay(String),
+    ReplayForward,
+    ReplayBackward,
+    ReplaySeek(usize),
+    ReplayTogglePlay,
+    ReplaySpeed(u32),
+    ReplayTick,
+    VerifyReplay,
+    ExportImage,
+    ImageExported(Option<Result<PathBuf, String>>),
+    ReplayGifCaption(bool),
+    ExportGif,
+    GifExported(Option<Result<PathBuf, String>>),
+    OpenEditor,
+    CloseEditor,
+    EditorCycle(usize, bool),
+    EditorWidth(String),
+    EditorHeight(String),
+    EditorResize,
+    EditorScore(String),
+    EditorClear,
+    EditorFill,
+    EditorPlay,
+    OpenRace,
+    CloseRace,
+    RaceLength(usize),
+    RaceStart,
+    RaceRestart,
+    RaceSaveRecords,
+    RaceTick,
+    OpenLan,
+    CloseLan,
+    LanAddress(String),
+    LanPort(String),
+    LanMinutes(usize),
+    LanHost,
+    LanJoin,
+    LanLeave,
+    LanEvent(lan::Event),
+    LanTick,
     Event(Event),
 }
 
@@ -280,12 +339,14 @@ pub enum Message {
 pub enum ContextPage {
     #[default]
     About,
+    HighScores,
 }
 
 impl ContextPage {
     fn title(&self) -> String {
         match self {
             Self::About => fl!("about"),
+            Self::HighScores => fl!("high-scores"),
         }
     }
 }
@@ -293,6 +354,7 @@ impl ContextPage {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
     About,
+    HighScores,
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -301,6 +363,7 @@ impl menu::action::MenuAction for MenuAction {
     fn message(&self) -> Self::Message {
         match self {
             MenuAction::About => Message::ToggleContextPage(ContextPage::About),
+            MenuAction::HighScores => Message::ToggleContextPage(ContextPage::HighScores),
         }
     }
 }
@@ -343,6 +406,15 @@ impl Application for App2048 {
             context_page: ContextPage::default(),
             key_binds: HashMap::new(),
             game: Game::new(),
+            high_scores: HighScores::load().unwrap_or_default(),
+            puzzles: Puzzle::all(),
+            solved: SolvedPuzzles::load().unwrap_or_default(),
+            daily_results: DailyResults::load().unwrap_or_default(),
+            picking_puzzle: false,
+            replay: None,
+            editor: None,
+            race: None,
+            lan: None,
         };
 
         let command = app.update_titles();
@@ -356,7 +428,10 @@ impl Application for App2048 {
             menu::root(fl!("view")),
             menu::items(
                 &self.key_binds,
-                vec![menu::Item::Button(fl!("about"), MenuAction::About)],
+                vec![
+                    menu::Item::Button(fl!("high-scores"), MenuAction::HighScores),
+                    menu::Item::Button(fl!("about"), MenuAction::About),
+                ],
             ),
         )]);
 
@@ -370,8 +445,18 @@ impl Application for App2048 {
     ///
     /// To get a better sense of which widgets are available, check out the `widget` module.
     fn view(&self) -> Element<Message> {
-        if self.game.menu.start_pressed {
-            playfield(&self.game)
+        if let Some(replay) = &self.replay {
+            replay::view(replay)
+        } else if let Some(editor) = &self.editor {
+            editor::view(editor)
+        } else if let Some(race) = &self.race {
+            race::view(race)
+        } else if let Some(lan) = &self.lan {
+            lan::view(lan)
+        } else if self.picking_puzzle {
+            puzzles::view(&self.puzzles, &self.solved)
+        } else if self.game.menu.start_pressed {
+            playfield(&self.game, &self.daily_results)
         } else {
             menu(&self.game)
         }
@@ -385,7 +470,43 @@ impl Application for App2048 {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        event::listen().map(Message::Event)
+        let replay_tick = match &self.replay {
+            Some(replay) if replay.playing => {
+                cosmic::iced::time::every(time::Duration::from_millis(replay.speed.into()))
+                    .map(|_| Message::ReplayTick)
+            }
+            _ => Subscription::none(),
+        };
+        let clock_tick = match &self.game.clock {
+            Some(clock) if clock.last_tick.is_some() && !self.game.finished => {
+                cosmic::iced::time::every(time::Duration::from_millis(100))
+                    .map(|_| Message::ClockTick)
+            }
+            _ => Subscription::none(),
+        };
+        let race_tick = match &self.race {
+            Some(race) if race.running() => {
+                cosmic::iced::time::every(time::Duration::from_millis(200))
+                    .map(|_| Message::RaceTick)
+            }
+            _ => Subscription::none(),
+        };
+        let lan = match &self.lan {
+            Some(lan) if lan.running() => Subscription::batch([
+                lan::subscription(lan),
+                cosmic::iced::time::every(time::Duration::from_millis(200))
+                    .map(|_| Message::LanTick),
+            ]),
+            Some(lan) => lan::subscription(lan),
+            None => Subscription::none(),
+        };
+        Subscription::batch([
+            event::listen().map(Message::Event),
+            replay_tick,
+            clock_tick,
+            race_tick,
+            lan,
+        ])
     }
 
     /// Application messages are handled here. The application state can be modified based on
@@ -411,34 +532,638 @@ impl Application for App2048 {
                 self.set_context_title(context_page.title());
             }
             Message::GotoMenu => {
+                // An autoplay search still in flight must not start a second loop later on.
+                let autoplay_pending = self.game.autoplay_pending;
                 self.game = Game::new();
+                self.game.autoplay_pending = autoplay_pending;
             }
             Message::InputWidth(input) => self.game.menu.width_inptut = input,
             Message::InputHeight(input) => self.game.menu.height_inptut = input,
-            Message::StartPressed => {
-                self.game.menu.width = self.game.menu.width_inptut.parse().unwrap();
-                self.game.menu.height = self.game.menu.height_inptut.parse().unwrap();
-                self.game.board = Board::new(self.game.menu.width, self.game.menu.height);
-                self.game.old_board = self.game.board.clone();
-                self.game.menu.start_pressed = true;
+            Message::Opponent(index) => {
+                let mut rules = SpawnRules::parse(&self.game.menu.spawn_input).unwrap_or_default();
+                rules.opponent = index.checked_sub(1).map(|index| Malice::ALL[index]);
+                return self.update(Message::InputSpawn(rules.to_string()));
+            }
+            Message::Shape(index) => {
+                let mut rules = SpawnRules::parse(&self.game.menu.spawn_input).unwrap_or_default();
+                rules.shape = Shape::ALL[index];
+                return self.update(Message::InputSpawn(rules.to_string()));
+            }
+            Message::SpawnPreset(index) => {
+                if let Some(rules) = self.game.menu.spawn_presets.get(index) {
+                    self.game.menu.spawn_input = rules.to_string();
+                    self.game.menu.spawn_preset = Some(index);
+                }
+            }
+            Message::InputSpawn(input) => {
+                self.game.menu.spawn_preset = self
+                    .game
+                    .menu
+                    .spawn_presets
+                    .iter()
+                    .position(|rules| rules.to_string() == input);
+                self.game.menu.spawn_input = input;
+            }
+            Message::ClockLength(index) => self.game.menu.clock = index,
+            Message::StartDaily => self.start_daily(),
+            Message::ShareDaily => {
+                let Some(result) = self.game.daily.and_then(|day| self.daily_results.get(day))
+                else {
+                    return Command::none();
+                };
+                let streak = self.daily_results.streak(result.day);
+                self.game.status = String::from("Result copied");
+                return cosmic::iced::clipboard::write(daily::share_text(
+                    result,
+                    streak,
+                    &self.saved_game(),
+                ));
+            }
+            Message::OpenPuzzles => self.picking_puzzle = true,
+            Message::ClosePuzzles => self.picking_puzzle = false,
+            Message::PlayPuzzle(index) => {
+                if let Some(puzzle) = self.puzzles.get(index) {
+                    self.start_puzzle(puzzle.clone());
+                }
+            }
+            Message::ClockTick => {
+                if let Some(clock) = &mut self.game.clock {
+                    if clock.tick() {
+                        self.game_over();
+                    }
+                }
+            }
+            Message::Event(Event::Window(_, window::Event::Unfocused)) => {
+                if let Some(clock) = self.game.clock.as_mut().filter(|_| !self.game.finished) {
+                    clock.pause(true);
+                }
+            }
+            Message::Event(Event::Window(_, window::Event::Focused)) => {
+                if let Some(clock) = self.game.clock.as_mut().filter(|_| !self.game.finished) {
+                    clock.pause(false);
+                }
+            }
+            Message::StartPressed => self.start(false),
+            Message::StartHotSeat => self.start(true),
+            Message::Place(id) => {
+                let Some(Turn::Place(value)) = self.game.hot_seat else {
+                    return Command::none();
+                };
+                if self.game.board.place(id, value).is_none() {
+                    return Command::none();
+                }
+                self.game.hot_seat = Some(Turn::Slide);
+                if self.game.board.is_stuck(
+                    self.game.menu.height,
+                    self.game.menu.width,
+                    self.game.spawn.movement,
+                ) {
+                    self.game_over();
+                }
             }
 
             Message::Reset => {
-                self.game.board = Board::new(self.game.menu.width, self.game.menu.height);
-                self.game.old_board = self.game.board.clone();
+                if let Some(puzzle) = self.game.puzzle.clone() {
+                    self.start_puzzle(puzzle);
+                } else if self.game.daily.is_some() {
+                    self.start_daily();
+                } else if self.game.hot_seat.is_some() {
+                    self.new_hot_seat_board();
+                } else {
+                    self.new_board();
+                }
+                self.game.hint = None;
+                self.game.autoplay = false;
+            }
+            Message::Event(Event::Keyboard(keyboard::Event::KeyPressed { key, .. }))
+                if self.replay.is_some() =>
+            {
+                // The replay only reacts to its own controls.
+                return match key {
+                    keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
+                        self.update(Message::ReplayBackward)
+                    }
+                    keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
+                        self.update(Message::ReplayForward)
+                    }
+                    keyboard::Key::Named(keyboard::key::Named::Space) => {
+                        self.update(Message::ReplayTogglePlay)
+                    }
+                    _ => Command::none(),
+                };
+            }
+            Message::Event(Event::Keyboard(keyboard::Event::KeyPressed { key, .. }))
+                if self.editor.is_some() =>
+            {
+                // Keys type into the selected cell instead of playing.
+                let Some(editor) = &mut self.editor else {
+                    return Command::none();
+                };
+                match key {
+                    keyboard::Key::Character(c) if c.as_str() == "." => editor.erase(),
+                    keyboard::Key::Character(c) if c.as_str() == "#" => editor.block(),
+                    keyboard::Key::Character(c) => {
+                        c.chars()
+                            .filter(char::is_ascii_digit)
+                            .for_each(|digit| editor.type_digit(digit));
+                    }
+                    keyboard::Key::Named(keyboard::key::Named::Backspace) => editor.backspace(),
+                    keyboard::Key::Named(keyboard::key::Named::Delete) => editor.erase(),
+                    keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
+                        editor.move_selection(0, -1)
+                    }
+                    keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
+                        editor.move_selection(0, 1)
+                    }
+                    keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
+                        editor.move_selection(-1, 0)
+                    }
+                    keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
+                        editor.move_selection(1, 0)
+                    }
+                    _ => {}
+                }
+            }
+            Message::Event(Event::Keyboard(keyboard::Event::KeyPressed { key, .. }))
+                if self.race.is_some() =>
+            {
+                // Both key sets belong to the racers.
+                if let Some(race) = &mut self.race {
+                    race.key(&key);
+                }
+            }
+            Message::Event(Event::Keyboard(keyboard::Event::KeyPressed { key, .. }))
+                if self.lan.is_some() =>
+            {
+                if let Some(lan) = &mut self.lan {
+                    lan.key(&key);
+                }
             }
             Message::Event(Event::Keyboard(keyboard::Event::KeyPressed { key, .. })) => {
-                let old_board = self.game.board.clone();
-                self.game.board.move_tile_content(
-                    &key,
-                    self.game.menu.height,
-                    self.game.menu.width,
+                let grid = self.game.spawn.movement.grid;
+                // Hex boards move with letters too, which wins over the shortcuts.
+                let direction = key_to_direction(&key, grid).or(match grid {
+                    Grid::Square => None,
+                    Grid::Hex => letter_to_direction(&key, grid),
+                });
+                if let (None, keyboard::Key::Character(c)) = (direction, &key) {
+                    if !self.game.menu.start_pressed {
+                        return Command::none();
+                    }
+                    return match c.as_str() {
+                        "h" => self.update(Message::Hint),
+                        "p" => self.update(Message::ToggleAutoplay),
+                        "u" => self.update(Message::Back),
+                        "s" => self.update(Message::Save),
+                        "l" => self.update(Message::Load),
+                        "r" => self.update(Message::Reset),
+                        "c" => self.update(Message::CopyBoard),
+                        "v" => self.update(Message::PasteBoard),
+                        _ => Command::none(),
+                    };
+                }
+                let Some(direction) = direction else {
+                    return Command::none();
+                };
+                if let Some(Turn::Place(_)) = self.game.hot_seat {
+                    self.game.status = String::from("Player two places a tile first");
+                    return Command::none();
+                }
+                // Pressing an arrow key takes over from the autoplay.
+                self.game.autoplay = false;
+                self.make_move(direction);
+            }
+            Message::Back => {
+                // The clock cannot be wound back, nor can a daily game that counts.
+                if (self.game.finished && self.game.clock.is_some()) || self.game.daily_scored {
+                    return Command::none();
+                }
+                if self.game.board != self.game.old_board {
+                    if let Some(record) = &mut self.game.record {
+                        record.moves.pop();
+                    }
+                    // Taking a move back gives it back to the puzzle.
+                    if self.game.puzzle.is_some() {
+                        self.game.puzzle_moves -= 1;
+                        self.game.finished = false;
+                    }
+                }
+                self.game.board = self.game.old_board.clone();
+                self.game.score = self.game.old_score;
+                self.game.rng = self.game.old_rng.clone();
+                self.game.hint = None;
+                // The position before the last slide, whether or not its tile was placed yet.
+                if self.game.hot_seat.is_some() {
+                    self.game.hot_seat = Some(Turn::Slide);
+                }
+            }
+            Message::Hint => {
+                // Puzzles are solved without help.
+                if !self.game.menu.start_pressed
+                    || self.game.hint_pending
+                    || self.game.puzzle.is_some()
+                    || self.game.hot_seat.is_some_and(|turn| turn != Turn::Slide)
+                {
+                    return Command::none();
+                }
+                self.game.hint_pending = true;
+                self.game.hints_used += 1;
+                let board = self.game.board.clone();
+                let (height, width) = (self.game.menu.height, self.game.menu.width);
+                let rules = self.game.spawn.clone();
+                return Command::perform(
+                    async move {
+                        let direction =
+                            search(board.clone(), height, width, rules, HINT_BUDGET).await;
+                        (board, direction)
+                    },
+                    |(board, direction)| {
+                        cosmic::app::Message::App(Message::HintReady(board, direction))
+                    },
                 );
-                if old_board != self.game.board {
-                    self.game.old_board = old_board;
+            }
+            Message::HintReady(board, direction) => {
+                self.game.hint_pending = false;
+                // The player may have moved while the search was running.
+                if board == self.game.board {
+                    self.game.hint = direction;
+                }
+            }
+            Message::ToggleAutoplay => {
+                // There is nobody to place the tiles for, and daily games and
+                // puzzles are played by hand.
+                if !self.game.menu.start_pressed
+                    || self.game.hot_seat.is_some()
+                    || self.game.daily_scored
+                    || self.game.puzzle.is_some()
+                {
+                    return Command::none();
+                }
+                self.game.autoplay = !self.game.autoplay;
+                return self.autoplay_step();
+            }
+            Message::AutoplayDelay(delay) => self.game.autoplay_delay = delay,
+            Message::AutoplayMove(board, direction) => {
+                self.game.autoplay_pending = false;
+                if !self.game.autoplay || board != self.game.board {
+                    return self.autoplay_step();
+                }
+                match direction {
+                    Some(direction) => self.make_move(direction),
+                    // No move changes the board, the game is over.
+                    None => self.game.autoplay = false,
+                }
+                return self.autoplay_step();
+            }
+            Message::Save => {
+                self.game.status = match self.saved_game().store() {
+                    Ok(()) => String::from("Game saved"),
+                    Err(error) => format!("Could not save the game: {error}"),
+                };
+            }
+            Message::Load => match SavedGame::load() {
+                Ok(saved) => {
+                    self.resume(saved);
+                    self.game.status = String::from("Game loaded");
+                }
+                Err(error) => self.game.status = format!("Could not load the game: {error}"),
+            },
+            Message::CopyBoard => {
+                self.game.status = String::from("Position copied");
+                return cosmic::iced::clipboard::write(self.saved_game().compact());
+            }
+            Message::CopyEmoji => {
+                self.game.status = String::from("Emoji grid copied");
+                return cosmic::iced::clipboard::write(self.saved_game().emoji_grid());
+            }
+            Message::PasteBoard => {
+                return cosmic::iced::clipboard::read(|contents| {
+                    cosmic::app::Message::App(Message::Pasted(contents))
+                });
+            }
+            Message::Pasted(contents) => {
+                match SavedGame::from_clipboard(contents.as_deref().unwrap_or_default()) {
+                    Ok(saved) => {
+                        self.resume(saved);
+                        self.game.status = String::from("Position pasted");
+                    }
+                    Err(error) => {
+                        self.game.status = format!("Could not paste the position: {error}")
+                    }
+                }
+            }
+            Message::SaveRecord => {
+                self.game.status = match &self.game.record {
+                    Some(record) => {
+                        let mut record = record.clone();
+                        record.score = Some(self.game.score);
+                        record.board = Some(self.game.board.clone());
+                        match record.store() {
+                            Ok(path) => format!("Record written to {}", path.display()),
+                            Err(error) => format!("Could not write the record: {error}"),
+                        }
+                    }
+                    None => String::from("Games resumed from a save file are not recorded"),
                 };
             }
-            Message::Back => self.game.board = self.game.old_board.clone(),
+            Message::OpenReplay => {
+                self.game.autoplay = false;
+                self.replay = Some(replay::Replay::new());
+            }
+            Message::CloseReplay => self.replay = None,
+            Message::ReplayPath(path) => {
+                if let Some(replay) = &mut self.replay {
+                    replay.path = path;
+                }
+            }
+            Message::LoadReplay(path) => {
+                if let Some(replay) = &mut self.replay {
+                    replay.load(path);
+                }
+            }
+            Message::ReplayForward | Message::ReplayTick => {
+                if let Some(replay) = &mut self.replay {
+                    replay.forward();
+                }
+            }
+            Message::ReplayBackward => {
+                if let Some(replay) = &mut self.replay {
+                    replay.backward();
+                }
+            }
+            Message::ReplaySeek(index) => {
+                if let Some(replay) = &mut self.replay {
+                    replay.seek(index);
+                }
+            }
+            Message::ReplayTogglePlay => {
+                if let Some(replay) = &mut self.replay {
+                    if replay.current == replay.last() {
+                        replay.current = 0;
+                    }
+                    replay.playing = !replay.playing && replay.last() > 0;
+                }
+            }
+            Message::VerifyReplay => {
+                if let Some(replay) = &mut self.replay {
+                    replay.verify();
+                }
+            }
+            Message::ReplaySpeed(speed) => {
+                if let Some(replay) = &mut self.replay {
+                    replay.speed = speed;
+                }
+            }
+            Message::ExportImage => {
+                let board = self.game.board.clone();
+                let (height, width) = (self.game.menu.height, self.game.menu.width);
+                let score = self.game.score;
+                let movement = self.game.spawn.movement;
+                return Command::perform(
+                    async move {
+                        let file = rfd::AsyncFileDialog::new()
+                            .set_title("Export image")
+                            .set_file_name(format!("2048-{score}.png"))
+                            .add_filter("PNG image", &["png"])
+                            .add_filter("SVG image", &["svg"])
+                            .save_file()
+                            .await?;
+                        let path = file.path().to_path_buf();
+                        Some(
+                            export::write_image(&path, &board, height, width, movement, score)
+                                .map(|()| path)
+                                .map_err(|error| error.to_string()),
+                        )
+                    },
+                    |result| cosmic::app::Message::App(Message::ImageExported(result)),
+                );
+            }
+            Message::ImageExported(result) => match result {
+                Some(Ok(path)) => self.game.status = format!("Image saved to {}", path.display()),
+                Some(Err(error)) => {
+                    self.game.status = format!("Could not export the image: {error}")
+                }
+                // The file chooser was cancelled.
+                None => {}
+            },
+            Message::ReplayGifCaption(caption) => {
+                if let Some(replay) = &mut self.replay {
+                    replay.gif_caption = caption;
+                }
+            }
+            Message::ExportGif => {
+                let Some(replay) = &mut self.replay else {
+                    return Command::none();
+                };
+                let Some(record) = replay.record.clone() else {
+                    return Command::none();
+                };
+                replay.playing = false;
+                let options = AnimationOptions {
+                    frame_delay: replay.speed,
+                    caption: replay.gif_caption,
+                };
+                return Command::perform(
+                    async move {
+                        let file = rfd::AsyncFileDialog::new()
+                            .set_title("Export GIF")
+                            .set_file_name(format!("2048-{}.gif", record.seed))
+                            .add_filter("GIF animation", &["gif"])
+                            .save_file()
+                            .await?;
+                        let path = file.path().to_path_buf();
+                        // Encoding a long game takes a while, keep it off the UI thread.
+                        let result = tokio::task::spawn_blocking(move || {
+                            let out = std::io::BufWriter::new(std::fs::File::create(&path)?);
+                            animation::write_gif(&record, &options, out)
+                                .map_err(std::io::Error::other)?;
+                            Ok::<PathBuf, std::io::Error>(path)
+                        })
+                        .await;
+                        Some(match result {
+                            Ok(written) => written.map_err(|error| error.to_string()),
+                            Err(error) => Err(error.to_string()),
+                        })
+                    },
+                    |result| cosmic::app::Message::App(Message::GifExported(result)),
+                );
+            }
+            Message::GifExported(result) => {
+                if let Some(replay) = &mut self.replay {
+                    match result {
+                        Some(Ok(path)) => {
+                            replay.status = format!("GIF saved to {}", path.display());
+                        }
+                        Some(Err(error)) => {
+                            replay.status = format!("Could not export the GIF: {error}");
+                        }
+                        // The file chooser was cancelled.
+                        None => {}
+                    }
+                }
+            }
+            Message::OpenEditor => {
+                self.game.autoplay = false;
+                // Start from the game in progress, or from an empty board of the menu's size.
+                let editor = if self.game.menu.start_pressed {
+                    editor::Editor::new(
+                        self.game.board.clone(),
+                        self.game.menu.height,
+                        self.game.menu.width,
+                        self.game.score,
+                        self.game.spawn.clone(),
+                    )
+                } else {
+                    let side = |input: &str| {
+                        input
+                            .parse()
+                            .ok()
+                            .filter(|side| (2..=MAX_SIDE).contains(side))
+                            .unwrap_or(4)
+                    };
+                    let width = side(&self.game.menu.width_inptut);
+                    let height = side(&self.game.menu.height_inptut);
+                    editor::Editor::new(
+                        Board::from_contents(vec![None; width * height]),
+                        height,
+                        width,
+                        0,
+                        SpawnRules::parse(&self.game.menu.spawn_input).unwrap_or_default(),
+                    )
+                };
+                self.editor = Some(editor);
+            }
+            Message::CloseEditor => self.editor = None,
+            Message::EditorCycle(id, up) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.cycle(id, up);
+                }
+            }
+            Message::EditorWidth(input) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.width_input = input;
+                }
+            }
+            Message::EditorHeight(input) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.height_input = input;
+                }
+            }
+            Message::EditorResize => {
+                if let Some(editor) = &mut self.editor {
+                    editor.resize();
+                }
+            }
+            Message::EditorScore(input) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.score_input = input;
+                }
+            }
+            Message::EditorClear => {
+                if let Some(editor) = &mut self.editor {
+                    editor.clear();
+                }
+            }
+            Message::EditorFill => {
+                if let Some(editor) = &mut self.editor {
+                    editor.fill();
+                }
+            }
+            Message::EditorPlay => {
+                let Some(editor) = &mut self.editor else {
+                    return Command::none();
+                };
+                match editor.position() {
+                    Ok(saved) => {
+                        self.editor = None;
+                        self.resume(saved);
+                        self.game.status = String::from("Playing from the edited position");
+                    }
+                    Err(error) => editor.status = error,
+                }
+            }
+            Message::OpenRace => {
+                if let Some((width, height, rules)) = self.menu_settings() {
+                    self.race = Some(race::Race::new(width, height, rules, thread_rng().gen(), 1));
+                }
+            }
+            Message::CloseRace => self.race = None,
+            Message::RaceLength(length) => {
+                if let Some(race) = &mut self.race {
+                    race.set_minutes(length);
+                }
+            }
+            Message::RaceStart => {
+                if let Some(race) = &mut self.race {
+                    race.start();
+                }
+            }
+            Message::RaceRestart => {
+                if let Some(race) = &mut self.race {
+                    *race = race.restart(thread_rng().gen());
+                }
+            }
+            Message::RaceSaveRecords => {
+                if let Some(race) = &mut self.race {
+                    race.store_records();
+                }
+            }
+            Message::RaceTick => {
+                if let Some(race) = &mut self.race {
+                    race.tick();
+                }
+            }
+            Message::OpenLan => {
+                if let Some((width, height, rules)) = self.menu_settings() {
+                    self.lan = Some(lan::Lan::new(width, height, rules));
+                }
+            }
+            Message::CloseLan => {
+                if let Some(lan) = &mut self.lan {
+                    lan.leave();
+                }
+                self.lan = None;
+            }
+            Message::LanAddress(address) => {
+                if let Some(lan) = &mut self.lan {
+                    lan.address = address;
+                }
+            }
+            Message::LanPort(port) => {
+                if let Some(lan) = &mut self.lan {
+                    lan.port = port;
+                }
+            }
+            Message::LanMinutes(minutes) => {
+                if let Some(lan) = &mut self.lan {
+                    lan.minutes = minutes;
+                }
+            }
+            Message::LanHost => {
+                if let Some(lan) = &mut self.lan {
+                    lan.host(thread_rng().gen(), thread_rng().gen());
+                }
+            }
+            Message::LanJoin => {
+                if let Some(lan) = &mut self.lan {
+                    lan.join(thread_rng().gen());
+                }
+            }
+            Message::LanLeave => {
+                if let Some(lan) = &mut self.lan {
+                    lan.leave();
+                }
+            }
+            Message::LanEvent(event) => {
+                if let Some(lan) = &mut self.lan {
+                    lan.event(event);
+                }
+            }
+            Message::LanTick => {
+                if let Some(lan) = &mut self.lan {
+                    lan.tick();
+                }
+            }
             Message::Event(_) => {}
         }
         Command::none()
@@ -452,6 +1177,7 @@ impl Application for App2048 {
 
         Some(match self.context_page {
             ContextPage::About => self.about(),
+            ContextPage::HighScores => self.high_scores(),
         })
     }
 }
@@ -482,6 +1208,370 @@ impl App2048 {
             .into()
     }
 
+    /// The best games of every mode, one table each.
+    pub fn high_scores(&self) -> Element<Message> {
+        let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+        let modes = self.high_scores.modes();
+        if modes.is_empty() {
+            return text("No finished games yet").into();
+        }
+        modes
+            .into_iter()
+            .fold(widget::column(), |column, mode| {
+                let table = self.high_scores.table(mode).into_iter().enumerate().fold(
+                    widget::column().push(widget::text::title4(mode)),
+                    |table, (index, entry)| {
+                        let hints = match entry.hints {
+                            0 => String::new(),
+                            1 => String::from(", 1 hint"),
+                            hints => format!(", {hints} hints"),
+                        };
+                        table.push(text(format!(
+                            "{}. {}  (tile {}, {}x{}{hints})",
+                            index + 1,
+                            entry.score,
+                            entry.max_tile,
+                            entry.width,
+                            entry.height
+                        )))
+                    },
+                );
+                column.push(table.spacing(space_xxs))
+            })
+            .spacing(20)
+            .into()
+    }
+
+    /// Hands the score of a finished game in to the high scores, once.
+    fn game_over(&mut self) {
+        if self.game.finished {
+            return;
+        }
+        self.game.finished = true;
+        self.game.autoplay = false;
+        if self.game.hot_seat.is_some() {
+            self.game.status = format!(
+                "Game over, player two stopped player one at {}",
+                self.game.score
+            );
+            return;
+        }
+        let over = match &self.game.clock {
+            Some(clock) if clock.is_up() => "Time is up",
+            _ => "Game over",
+        };
+        if self.game.daily_scored {
+            self.game.status = match self.daily_progress() {
+                Ok(()) => String::from("Game over, press Share to copy your result"),
+                Err(error) => format!("Game over, could not save the result: {error}"),
+            };
+            return;
+        }
+        if self.game.daily.is_some() {
+            self.game.status = String::from("Game over, practice games do not count");
+            return;
+        }
+        // Only games dealt from a seed count, not edited or pasted positions.
+        if self.game.record.is_none() {
+            self.game.status = String::from(over);
+            return;
+        }
+        let mode = self.mode_name();
+        let rank = self.high_scores.add(self.game.high_score(mode.clone()));
+        self.game.status = match (rank, self.high_scores.store()) {
+            (_, Err(error)) => format!("{over}, could not save the high scores: {error}"),
+            (Some(rank), Ok(())) => format!("{over}, number {rank} in the {mode} high scores"),
+            (None, Ok(())) => String::from(over),
+        };
+    }
+
+    /// Names the kind of game for the high scores, time-attack games by their length.
+    fn mode_name(&self) -> String {
+        let mode = self.game.spawn.mode_name();
+        match &self.game.clock {
+            Some(clock) => format!("{mode}, {} min time attack", clock.length.as_secs() / 60),
+            None => mode,
+        }
+    }
+
+    /// The board size and spawn rules picked in the menu. Sizes that are not
+    /// numbers from 2 to `MAX_SIDE` fall back on 4.
+    fn menu_settings(&mut self) -> Option<(usize, usize, SpawnRules)> {
+        let side = |input: &str| {
+            input
+                .parse()
+                .ok()
+                .filter(|side| (2..=MAX_SIDE).contains(side))
+                .unwrap_or(4)
+        };
+        match SpawnRules::parse(&self.game.menu.spawn_input) {
+            Ok(rules) => Some((
+                side(&self.game.menu.width_inptut),
+                side(&self.game.menu.height_inptut),
+                rules,
+            )),
+            Err(error) => {
+                self.game.status = format!("Invalid spawn rules: {error}");
+                None
+            }
+        }
+    }
+
+    /// Starts a game with the size and spawn rules picked in the menu.
+    fn start(&mut self, hot_seat: bool) {
+        let Some((width, height, rules)) = self.menu_settings() else {
+            return;
+        };
+        self.game.spawn = rules;
+        self.game.menu.width = width;
+        self.game.menu.height = height;
+        if hot_seat {
+            self.new_hot_seat_board();
+        } else {
+            self.new_board();
+        }
+        self.game.menu.start_pressed = true;
+    }
+
+    /// Deals the starting tiles of a hot-seat game. Player two picks where every
+    /// later tile goes, so the game cannot be recorded.
+    fn new_hot_seat_board(&mut self) {
+        self.game.rng = StdRng::from_entropy();
+        let board = self.game.spawn.start_board(
+            self.game.menu.height,
+            self.game.menu.width,
+            &mut self.game.rng,
+        );
+        self.game.begin(board, 0);
+        self.game.old_rng = self.game.rng.clone();
+        self.game.record = None;
+        self.game.hot_seat = Some(Turn::Slide);
+        self.game.clock = None;
+        self.game.daily = None;
+        self.game.daily_scored = false;
+    }
+
+    /// Starts today's daily game. Only the first attempt of the day counts.
+    fn start_daily(&mut self) {
+        let day = daily::today();
+        self.game.spawn = SpawnRules::classic();
+        self.game.menu.width = daily::SIDE;
+        self.game.menu.height = daily::SIDE;
+        self.game.menu.width_inptut = daily::SIDE.to_string();
+        self.game.menu.height_inptut = daily::SIDE.to_string();
+        self.game.menu.clock = 0;
+        self.deal(daily::seed(day));
+        self.game.hint = None;
+        self.game.autoplay = false;
+        self.game.hot_seat = None;
+        self.game.puzzle = None;
+        if let Some(record) = &mut self.game.record {
+            record.mode = String::from("daily");
+        }
+        self.game.daily = Some(day);
+        self.game.daily_scored = self.daily_results.get(day).is_none();
+        self.game.menu.start_pressed = true;
+        self.game.status = if !self.game.daily_scored {
+            format!(
+                "You played the game of {} already, this one is for practice",
+                daily::date(day)
+            )
+        } else {
+            match self.daily_progress() {
+                Ok(()) => format!("Daily game of {}, this attempt counts", daily::date(day)),
+                Err(error) => format!("Could not save the daily result: {error}"),
+            }
+        };
+    }
+
+    /// Writes down how far the day's scored attempt got.
+    fn daily_progress(&mut self) -> std::io::Result<()> {
+        let Some(day) = self.game.daily.filter(|_| self.game.daily_scored) else {
+            return Ok(());
+        };
+        self.daily_results.record(DailyResult {
+            day,
+            score: self.game.score,
+            max_tile: self.game.board.max_tile(),
+            moves: self
+                .game
+                .record
+                .as_ref()
+                .map_or(0, |record| record.moves.len()),
+            finished: self.game.finished,
+        });
+        self.daily_results.store()
+    }
+
+    /// Deals a board from a fresh seed and starts recording the game.
+    fn new_board(&mut self) {
+        self.deal(thread_rng().gen());
+    }
+
+    /// Deals a board from `seed` and starts recording the game.
+    fn deal(&mut self, seed: u64) {
+        let mut record = GameRecord::new(self.game.menu.width, self.game.menu.height, seed);
+        record.spawn = self.game.spawn.clone();
+        self.game.clock = self.game.menu.clock.checked_sub(1).map(|index| {
+            Countdown::new(time::Duration::from_secs(60 * TIME_ATTACK_MINUTES[index]))
+        });
+        if self.game.clock.is_some() {
+            record.mode = String::from("time-attack");
+        }
+        self.game.rng = record.rng();
+        let board = record.start_board(&mut self.game.rng);
+        self.game.begin(board, 0);
+        self.game.old_rng = self.game.rng.clone();
+        self.game.record = Some(record);
+        self.game.daily = None;
+        self.game.daily_scored = false;
+    }
+
+    fn saved_game(&self) -> SavedGame {
+        SavedGame {
+            width: self.game.menu.width,
+            height: self.game.menu.height,
+            score: self.game.score,
+            spawn: self.game.spawn.clone(),
+            board: self.game.board.clone(),
+        }
+    }
+
+    /// Continues from a position that was not dealt from a seed, so it is not recorded.
+    fn resume(&mut self, saved: SavedGame) {
+        self.game.autoplay = false;
+        self.game.menu.width = saved.width;
+        self.game.menu.height = saved.height;
+        self.game.menu.width_inptut = saved.width.to_string();
+        self.game.menu.height_inptut = saved.height.to_string();
+        self.game.menu.start_pressed = true;
+        self.game.begin(saved.board, saved.score);
+        self.game.spawn = saved.spawn;
+        self.game.record = None;
+        self.game.hot_seat = None;
+        self.game.clock = None;
+        self.game.puzzle = None;
+        self.game.daily = None;
+        self.game.daily_scored = false;
+    }
+
+    /// Plays `puzzle` from its start.
+    fn start_puzzle(&mut self, puzzle: Puzzle) {
+        self.resume(SavedGame {
+            width: puzzle.width,
+            height: puzzle.height,
+            score: 0,
+            spawn: puzzle.spawn.clone(),
+            board: puzzle.board.clone(),
+        });
+        self.game.rng = puzzle.rng();
+        self.game.old_rng = self.game.rng.clone();
+        self.game.status = puzzle.task();
+        self.game.puzzle = Some(puzzle);
+        self.game.puzzle_moves = 0;
+        self.picking_puzzle = false;
+    }
+
+    /// Counts a move made on a puzzle, which ends it once solved or out of moves.
+    fn puzzle_move(&mut self) {
+        let Some(puzzle) = &self.game.puzzle else {
+            return;
+        };
+        self.game.puzzle_moves += 1;
+        if puzzle.is_solved_by(&self.game.board) {
+            self.game.finished = true;
+            self.solved.0.insert(puzzle.id.clone());
+            self.game.status = match self.solved.store() {
+                Ok(()) => format!("Solved in {} moves!", self.game.puzzle_moves),
+                Err(error) => format!("Solved, but could not save it: {error}"),
+            };
+        } else if self.game.puzzle_moves >= puzzle.moves {
+            self.game.finished = true;
+            self.game.status = String::from("Out of moves, press Reset to try again");
+        }
+    }
+
+    /// Slides the board in `direction`, remembering the previous position for `Back`.
+    fn make_move(&mut self, direction: Direction) {
+        if self.game.clock.as_ref().is_some_and(Countdown::is_up)
+            || (self.game.puzzle.is_some() && self.game.finished)
+        {
+            return;
+        }
+        let old_board = self.game.board.clone();
+        let old_rng = self.game.rng.clone();
+        let (height, width) = (self.game.menu.height, self.game.menu.width);
+        let moved = match self.game.hot_seat {
+            // Player two places the tile instead.
+            Some(_) => self
+                .game
+                .board
+                .slide(direction, height, width, self.game.spawn.movement)
+                .map(|points| Moved {
+                    points,
+                    spawned: vec![],
+                }),
+            None => self.game.board.move_with_rules(
+                direction,
+                height,
+                width,
+                &self.game.spawn,
+                &mut self.game.rng,
+            ),
+        };
+        if let Some(moved) = moved {
+            self.game.old_board = old_board;
+            self.game.old_rng = old_rng;
+            self.game.old_score = self.game.score;
+            self.game.score += moved.points;
+            self.game.hint = None;
+            if let Some(record) = &mut self.game.record {
+                record.moves.push(RecordedMove {
+                    direction,
+                    spawns: moved.spawned,
+                });
+            }
+            self.puzzle_move();
+            if let Err(error) = self.daily_progress() {
+                self.game.status = format!("Could not save the daily result: {error}");
+            }
+            if self.game.hot_seat.is_some() {
+                let value = self.game.spawn.pick_value(&mut self.game.rng);
+                self.game.hot_seat = Some(Turn::Place(value));
+                // A slide always leaves room for the next tile.
+                return;
+            }
+            if self.game.board.is_stuck(
+                self.game.menu.height,
+                self.game.menu.width,
+                self.game.spawn.movement,
+            ) {
+                self.game_over();
+            }
+        };
+    }
+
+    /// Schedules the next autoplay move, unless one is already on its way.
+    fn autoplay_step(&mut self) -> Command<Message> {
+        if !self.game.autoplay || self.game.autoplay_pending {
+            return Command::none();
+        }
+        self.game.autoplay_pending = true;
+        let board = self.game.board.clone();
+        let (height, width) = (self.game.menu.height, self.game.menu.width);
+        let delay = time::Duration::from_millis(self.game.autoplay_delay.into());
+        let rules = self.game.spawn.clone();
+        Command::perform(
+            async move {
+                tokio::time::sleep(delay).await;
+                let direction = search(board.clone(), height, width, rules, AUTOPLAY_BUDGET).await;
+                (board, direction)
+            },
+            |(board, direction)| cosmic::app::Message::App(Message::AutoplayMove(board, direction)),
+        )
+    }
+
     /// Updates the header and window titles.
     pub fn update_titles(&mut self) -> Command<Message> {
         let window_title = fl!("app-title");
@@ -492,120 +1582,340 @@ impl App2048 {
     }
 }
 
-fn playfield(game: &Game) -> widget::Container<'_, Message, cosmic::Theme> {
-    let tile_widget = |id: usize| {
-        match game.board.0[id] {
-            Tile {
-                tilecontent: Some(2),
-                ..
-            } => container(centralize_tile_content(text("2".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::gray1theme)),
-            Tile {
-                tilecontent: Some(4),
-                ..
-            } => container(centralize_tile_content(text("4".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::gray2theme)),
-            Tile {
-                tilecontent: Some(8),
-                ..
-            } => container(centralize_tile_content(text("8".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::orange1theme)),
-            Tile {
-                tilecontent: Some(16),
-                ..
-            } => container(centralize_tile_content(text("16".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::orange2theme)),
-            Tile {
-                tilecontent: Some(32),
-                ..
-            } => container(centralize_tile_content(text("32".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::red1theme)),
-            Tile {
-                tilecontent: Some(64),
-                ..
-            } => container(centralize_tile_content(text("64".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::red2theme)),
-            Tile {
-                tilecontent: Some(128),
-                ..
-            } => container(centralize_tile_content(text("128".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::yellow1theme)),
-            Tile {
-                tilecontent: Some(256),
-                ..
-            } => container(centralize_tile_content(text("256".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::yellow2theme)),
-            Tile {
-                tilecontent: Some(512),
-                ..
-            } => container(centralize_tile_content(text("512".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::yellow3theme)),
-            Tile {
-                tilecontent: Some(1024),
-                ..
-            } => container(centralize_tile_content(text("1024".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::yellow4theme)),
-            Tile {
-                tilecontent: Some(2048),
-                ..
-            } => container(centralize_tile_content(text("2048".to_string()).size(16)))
-                .style(theme::Container::custom(widget_colors::yellow5theme)),
-            Tile {
-                tilecontent: Some(content),
-                ..
-            } => container(centralize_tile_content(text(format!("{content}")).size(16)))
-                .style(theme::Container::custom(widget_colors::blacktheme)),
-            Tile {
-                tilecontent: None, ..
-            } => container("").style(theme::Container::custom(
-                widget_colors::secondary_with_rounder_corners,
-            )),
-        }
-        .center_x()
-        .center_y()
-        .height(50)
-        .width(50)
-    };
-    let playboard = (0..game.menu.height).fold(Grid::new(), |acc, row| {
-        let new_row = (0..game.menu.width).fold(Row::new(), |acc2, column| {
-            acc2.push(tile_widget(pair_to_index(row, column, game.menu.width)))
+/// Runs the solver on a blocking thread so that the view stays responsive.
+async fn search(
+    board: Board,
+    height: usize,
+    width: usize,
+    rules: SpawnRules,
+    budget: time::Duration,
+) -> Option<Direction> {
+    tokio::task::spawn_blocking(move || {
+        ai::best_direction_with(&board, height, width, &rules, budget)
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// The tiles of `board`, as drawn by both the playfield and the replay viewer.
+fn board_view(
+    board: &Board,
+    height: usize,
+    width: usize,
+    movement: Movement,
+) -> widget::Container<'_, Message, cosmic::Theme> {
+    board_layout(height, width, movement.grid, |id| {
+        tile_view(&board.0[id], movement.merge).into()
+    })
+}
+
+/// A small copy of `board`, for keeping an eye on an opponent.
+fn mini_board_view(
+    board: &Board,
+    height: usize,
+    width: usize,
+    movement: Movement,
+) -> widget::Container<'_, Message, cosmic::Theme> {
+    sized_board_layout(height, width, 22, movement.grid, |id| {
+        sized_tile_view(&board.0[id], movement.merge, 22, 8).into()
+    })
+}
+
+/// Lays out `height` rows of `width` cells, each one drawn by `cell`.
+fn board_layout<'a>(
+    height: usize,
+    width: usize,
+    grid: Grid,
+    cell: impl Fn(usize) -> Element<'a, Message>,
+) -> widget::Container<'a, Message, cosmic::Theme> {
+    sized_board_layout(height, width, 50, grid, cell)
+}
+
+/// Like `board_layout`, for cells of `size` pixels.
+fn sized_board_layout<'a>(
+    height: usize,
+    width: usize,
+    size: u16,
+    grid: Grid,
+    cell: impl Fn(usize) -> Element<'a, Message>,
+) -> widget::Container<'a, Message, cosmic::Theme> {
+    let step = usize::from(size) + 2;
+    if grid == Grid::Hex {
+        // Every column hangs half a cell below the one on its left, so the
+        // cells up-right and down-left of a cell touch it like on a hexagon.
+        let playboard = (0..width).fold(Row::new(), |acc, column| {
+            let cells = (0..height).fold(
+                widget::column().push(Space::with_height((step * column / 2) as f32)),
+                |acc2, row| acc2.push(cell(pair_to_index(row, column, width))),
+            );
+            acc.push(cells.spacing(2))
+        });
+        return container(playboard.spacing(2))
+            .style(theme::Container::Primary)
+            .width((step * width + 2) as f32)
+            .height((step * height + step * (width - 1) / 2 + 2) as f32)
+            .center_x()
+            .center_y()
+            .padding(0);
+    }
+    let playboard = (0..height).fold(widget::Grid::new(), |acc, row| {
+        let new_row = (0..width).fold(Row::new(), |acc2, column| {
+            acc2.push(cell(pair_to_index(row, column, width)))
         });
         acc.push(new_row.spacing(2).align_items(Alignment::Center))
             .insert_row()
     });
+    container(playboard.row_spacing(2).row_alignment(Alignment::Center))
+        .style(theme::Container::Primary)
+        .width((step * width + 2) as f32)
+        .height((step * height + 2) as f32)
+        .center_x()
+        .center_y()
+        .padding(0)
+}
+
+fn tile_view<'a>(tile: &Tile, merge: Merge) -> widget::Container<'a, Message, cosmic::Theme> {
+    sized_tile_view(tile, merge, 50, 16)
+}
+
+fn sized_tile_view<'a>(
+    tile: &Tile,
+    merge: Merge,
+    size: u16,
+    text_size: u16,
+) -> widget::Container<'a, Message, cosmic::Theme> {
+    let content = tile
+        .tilecontent
+        .map_or(String::new(), |content| content.to_string());
+    let style = if tile.hole {
+        widget_colors::hole_theme
+    } else if tile.blocked {
+        widget_colors::blocker_theme
+    } else {
+        tile_theme(tile.tilecontent.map(|content| merge.rule().rank(content)))
+    };
+    container(centralize_tile_content(text(content).size(text_size)))
+        .style(theme::Container::custom(style))
+        .center_x()
+        .center_y()
+        .height(size)
+        .width(size)
+}
+
+/// The colours of a tile of `rank`, see `MergeRule::rank`: 1 to 11 are the
+/// tiles from 2 to 2048 of the classic game.
+fn tile_theme(rank: Option<usize>) -> fn(&Theme) -> widget::container::Appearance {
+    match rank {
+        Some(1) => widget_colors::gray1theme,
+        Some(2) => widget_colors::gray2theme,
+        Some(3) => widget_colors::orange1theme,
+        Some(4) => widget_colors::orange2theme,
+        Some(5) => widget_colors::red1theme,
+        Some(6) => widget_colors::red2theme,
+        Some(7) => widget_colors::yellow1theme,
+        Some(8) => widget_colors::yellow2theme,
+        Some(9) => widget_colors::yellow3theme,
+        Some(10) => widget_colors::yellow4theme,
+        Some(11) => widget_colors::yellow5theme,
+        Some(_) => widget_colors::blacktheme,
+        None => widget_colors::secondary_with_rounder_corners,
+    }
+}
+
+fn playfield<'a>(
+    game: &'a Game,
+    daily_results: &DailyResults,
+) -> widget::Container<'a, Message, cosmic::Theme> {
     let menu_button = button("Menu")
         .on_press(Message::GotoMenu)
         .style(theme::Button::Suggested);
     let reset_button = button("Reset")
         .on_press(Message::Reset)
         .style(theme::Button::Destructive);
-    let back_button = button("Back").on_press(Message::Back);
+    let back_button = button("Back").on_press_maybe((!game.daily_scored).then_some(Message::Back));
+    let hint_button = button("Hint").on_press_maybe(game.puzzle.is_none().then_some(Message::Hint));
+    let save_button = button("Save").on_press(Message::Save);
+    let load_button = button("Load").on_press(Message::Load);
+    let record_button = button("Save record").on_press(Message::SaveRecord);
+    let export_button = button("Export image").on_press(Message::ExportImage);
+    let copy_button = button("Copy").on_press(Message::CopyBoard);
+    let emoji_button = button("Copy emoji").on_press(Message::CopyEmoji);
+    let paste_button = button("Paste").on_press(Message::PasteBoard);
+    let edit_button = button("Edit").on_press(Message::OpenEditor);
+    let autoplay_button = button(if game.autoplay { "Pause" } else { "Autoplay" }).on_press_maybe(
+        (game.hot_seat.is_none() && !game.daily_scored && game.puzzle.is_none())
+            .then_some(Message::ToggleAutoplay),
+    );
+    let share_button = button("Share")
+        .on_press_maybe((game.daily_scored && game.finished).then_some(Message::ShareDaily))
+        .style(theme::Button::Suggested);
+    let autoplay_speed = widget::row()
+        .push(text("Delay: "))
+        .push(widget::slider(0..=1000, game.autoplay_delay, Message::AutoplayDelay).width(150))
+        .push(text(format!("{} ms", game.autoplay_delay)))
+        .spacing(10)
+        .align_items(Alignment::Center);
+    let movement = game.spawn.movement;
+    let grid = movement.grid;
+    let hint_arrow = match game.hint {
+        Some(direction) => direction.arrow(grid),
+        None if game.hint_pending => "…",
+        None => "",
+    };
+    let turn = match game.hot_seat {
+        Some(Turn::Slide) => String::from("Player one: slide the tiles with the arrow keys"),
+        Some(Turn::Place(value)) => format!("Player two: click an empty cell to place a {value}"),
+        None => String::new(),
+    };
+    let board = match game.hot_seat {
+        Some(Turn::Place(value)) => board_layout(game.menu.height, game.menu.width, grid, |id| {
+            let tile = &game.board.0[id];
+            if !tile.is_empty() {
+                return tile_view(tile, movement.merge).into();
+            }
+            widget::mouse_area(tile_view(tile, movement.merge))
+                .on_press(Message::Place(id))
+                .into()
+        }),
+        _ => board_view(&game.board, game.menu.height, game.menu.width, movement),
+    };
+    // Player two picks the tiles in hot seat games, so there is nothing to preview.
+    let next_value = game
+        .hot_seat
+        .is_none()
+        .then(|| game.spawn.next_value(&game.rng))
+        .flatten();
+    let next_tile = match next_value {
+        Some(value) => {
+            let tile = Tile {
+                tilecontent: Some(value),
+                id: 0,
+                blocked: false,
+                hole: false,
+            };
+            widget::row()
+                .push(text("Next:"))
+                .push(sized_tile_view(&tile, movement.merge, 30, 12))
+                .spacing(10)
+                .align_items(Alignment::Center)
+        }
+        None => widget::row(),
+    };
+    let clock = game.clock.as_ref().map_or(String::new(), Countdown::view);
+    let mut buttons = widget::row()
+        .push(text(clock).size(24))
+        .push(menu_button)
+        .push(reset_button)
+        .push(back_button)
+        .push(hint_button)
+        .push(autoplay_button)
+        .push(save_button)
+        .push(load_button)
+        .push(record_button)
+        .push(export_button)
+        .push(copy_button)
+        .push(emoji_button)
+        .push(paste_button)
+        .push(edit_button)
+        .padding(20)
+        .spacing(20)
+        .align_items(Alignment::Center);
+    if game.daily.is_some() {
+        buttons = buttons.push(share_button);
+    }
     container(
         widget::column()
+            .push(buttons)
+            .push(text(&game.status))
+            .push(autoplay_speed)
+            .push(text(format!("Score: {}", game.score)).size(20))
+            .push(text(format!("Hints used: {}", game.hints_used)))
+            .push(text(if game.spawn == SpawnRules::classic() {
+                String::new()
+            } else {
+                game.spawn.mode_name()
+            }))
+            .push(text(turn).size(20))
+            .push(text(game.daily.map_or(String::new(), |day| {
+                let streak = daily_results.streak(daily::today());
+                let days = if streak == 1 { "day" } else { "days" };
+                format!("Daily {}, streak {streak} {days}", daily::date(day))
+            })))
+            .push(text(game.puzzle.as_ref().map_or(String::new(), |puzzle| {
+                format!(
+                    "{}: {}, {} left",
+                    puzzle.title,
+                    puzzle.task(),
+                    puzzle.moves - game.puzzle_moves
+                )
+            })))
             .push(
-                widget::row()
-                    .push(menu_button)
-                    .push(reset_button)
-                    .push(back_button)
-                    .padding(20)
-                    .spacing(20),
-            )
-            .align_items(Alignment::Center)
-            .push(
-                container(playboard.row_spacing(2).row_alignment(Alignment::Center))
-                    .style(theme::Container::Primary)
-                    .width((52 * game.menu.width + 2) as f32)
-                    .height((52 * game.menu.height + 2) as f32)
+                container(centralize_tile_content(text(hint_arrow).size(32)))
+                    .style(theme::Container::custom(widget_colors::hint_arrow_theme))
                     .center_x()
                     .center_y()
-                    .padding(0),
-            ),
+                    .height(40)
+                    .width(40),
+            )
+            .push(next_tile)
+            .align_items(Alignment::Center)
+            .push(board),
     )
     .padding(20)
     .center_x()
     .center_y()
 }
 
+/// The arrow keys. Hex boards move with the numpad keys around 5 instead,
+/// and up and down with the arrows as well.
+fn key_to_direction(key: &keyboard::Key, grid: Grid) -> Option<Direction> {
+    match (key, grid) {
+        (keyboard::Key::Named(keyboard::key::Named::ArrowUp), _) => Some(Direction::Up),
+        (keyboard::Key::Named(keyboard::key::Named::ArrowDown), _) => Some(Direction::Down),
+        (keyboard::Key::Named(keyboard::key::Named::ArrowLeft), Grid::Square) => {
+            Some(Direction::Left)
+        }
+        (keyboard::Key::Named(keyboard::key::Named::ArrowRight), Grid::Square) => {
+            Some(Direction::Right)
+        }
+        (keyboard::Key::Character(c), Grid::Hex) => match c.as_str() {
+            "7" => Some(Direction::Left),
+            "8" => Some(Direction::Up),
+            "9" => Some(Direction::UpRight),
+            "1" => Some(Direction::DownLeft),
+            "2" => Some(Direction::Down),
+            "3" => Some(Direction::Right),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// WASD, or the six keys QWE/ASD on hex boards.
+fn letter_to_direction(key: &keyboard::Key, grid: Grid) -> Option<Direction> {
+    let keyboard::Key::Character(c) = key else {
+        return None;
+    };
+    match (c.to_lowercase().as_str(), grid) {
+        ("w", _) => Some(Direction::Up),
+        ("s", _) => Some(Direction::Down),
+        ("a", Grid::Square) | ("q", Grid::Hex) => Some(Direction::Left),
+        ("d", _) => Some(Direction::Right),
+        ("e", Grid::Hex) => Some(Direction::UpRight),
+        ("a", Grid::Hex) => Some(Direction::DownLeft),
+        _ => None,
+    }
+}
+
+/// The names of the keys `key_to_direction` and `letter_to_direction` take.
+fn key_names(grid: Grid) -> (&'static str, &'static str) {
+    match grid {
+        Grid::Square => ("arrow keys", "WASD"),
+        Grid::Hex => ("numpad", "QWE/ASD"),
+    }
+}
+
 fn centralize_tile_content(tile_content: Text<Theme, Renderer>) -> Text<Theme, Renderer> {
     tile_content
         .horizontal_alignment(Horizontal::Center)
@@ -615,11 +1925,74 @@ fn centralize_tile_content(tile_content: Text<Theme, Renderer>) -> Text<Theme, R
 fn menu(game: &Game) -> widget::Container<'_, Message, cosmic::Theme> {
     let width_box = text_input("", &game.menu.width_inptut).on_input(Message::InputWidth);
     let height_box = text_input("", &game.menu.height_inptut).on_input(Message::InputHeight);
+    let spawn_preset = widget::dropdown(
+        &game.menu.spawn_preset_names,
+        game.menu.spawn_preset,
+        Message::SpawnPreset,
+    );
+    let spawn_box = text_input("2:9 4:1", &game.menu.spawn_input).on_input(Message::InputSpawn);
+    let opponent = SpawnRules::parse(&game.menu.spawn_input).ok().map(|rules| {
+        rules.opponent.map_or(0, |malice| {
+            1 + Malice::ALL
+                .iter()
+                .position(|&known| known == malice)
+                .unwrap()
+        })
+    });
+    let opponent_picker = widget::dropdown(&game.menu.opponent_names, opponent, Message::Opponent);
+    let shape = SpawnRules::parse(&game.menu.spawn_input).ok().map(|rules| {
+        Shape::ALL
+            .iter()
+            .position(|&known| known == rules.shape)
+            .unwrap()
+    });
+    let shape_picker = widget::dropdown(&game.menu.shape_names, shape, Message::Shape);
+    let clock_picker = widget::dropdown(
+        &game.menu.clock_names,
+        Some(game.menu.clock),
+        Message::ClockLength,
+    );
     let start_game_button = button(centralize_tile_content(text("START")))
         .on_press(Message::StartPressed)
         .style(theme::Button::Suggested)
         .width(96)
         .height(55);
+    let resume_button = button(centralize_tile_content(text("RESUME")))
+        .on_press(Message::Load)
+        .width(96)
+        .height(55);
+    let hot_seat_button = button(centralize_tile_content(text("HOT SEAT")))
+        .on_press(Message::StartHotSeat)
+        .width(96)
+        .height(55);
+    let race_button = button(centralize_tile_content(text("RACE")))
+        .on_press(Message::OpenRace)
+        .width(96)
+        .height(55);
+    let lan_button = button(centralize_tile_content(text("NETWORK")))
+        .on_press(Message::OpenLan)
+        .width(96)
+        .height(55);
+    let replay_button = button(centralize_tile_content(text("REPLAY")))
+        .on_press(Message::OpenReplay)
+        .width(96)
+        .height(55);
+    let paste_button = button(centralize_tile_content(text("PASTE")))
+        .on_press(Message::PasteBoard)
+        .width(96)
+        .height(55);
+    let daily_button = button(centralize_tile_content(text("DAILY")))
+        .on_press(Message::StartDaily)
+        .width(96)
+        .height(55);
+    let puzzles_button = button(centralize_tile_content(text("PUZZLES")))
+        .on_press(Message::OpenPuzzles)
+        .width(96)
+        .height(55);
+    let editor_button = button(centralize_tile_content(text("EDITOR")))
+        .on_press(Message::OpenEditor)
+        .width(96)
+        .height(55);
     container(
         widget::column()
             .push(
@@ -634,8 +2007,65 @@ fn menu(game: &Game) -> widget::Container<'_, Message, cosmic::Theme> {
                     .push(height_box.width(40))
                     .align_items(Alignment::Center),
             )
+            .push(
+                widget::row()
+                    .push(text("Spawns: "))
+                    .push(spawn_preset)
+                    .push(spawn_box.width(220))
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+            )
+            .push(
+                widget::row()
+                    .push(text("Opponent: "))
+                    .push(opponent_picker)
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+            )
+            .push(
+                widget::row()
+                    .push(text("Shape: "))
+                    .push(shape_picker)
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+            )
+            .push(
+                widget::row()
+                    .push(text("Time attack: "))
+                    .push(clock_picker)
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+            )
             .push(start_game_button)
+            .push(hot_seat_button)
+            .push(race_button)
+            .push(lan_button)
+            .push(resume_button)
+            .push(replay_button)
+            .push(paste_button)
+            .push(daily_button)
+            .push(puzzles_button)
+            .push(editor_button)
+            .push(text(&game.status))
             .align_items(Alignment::End)
             .spacing(20),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_games_start_without_hints() {
+        let mut game = Game::new();
+        game.hints_used = 3;
+        game.finished = true;
+        let board = SpawnRules::classic().start_board(4, 4, &mut thread_rng());
+        game.begin(board, 0);
+        let entry = game.high_score(String::from("Classic"));
+        assert_eq!(entry.hints, 0);
+        assert_eq!(entry.score, 0);
+        assert!(!game.finished);
+    }
+}
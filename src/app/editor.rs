@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use cosmic::iced::Alignment;
+use cosmic::theme;
+use cosmic::widget::{self, button, text, text_input};
+use cosmic_ext_2048::engine::board::{pair_to_index, Board};
+use cosmic_ext_2048::engine::save::{SavedGame, MAX_SIDE};
+use cosmic_ext_2048::engine::spawn::SpawnRules;
+
+use super::{board_layout, tile_view, widget_colors, Message};
+
+/// Clicking a cell cycles through the empty cell, this many of the smallest
+/// tiles (2 up to 131072 when doubling), a blocker and a hole.
+const CYCLE_RANKS: usize = 17;
+
+/// What a cell can be turned into, in the order clicking steps through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    /// The tile of this rank, see `MergeRule::rank`.
+    Tile(usize),
+    Blocker,
+    Hole,
+}
+
+/// State of the board editor, where positions are set up by hand.
+pub struct Editor {
+    pub width: usize,
+    pub height: usize,
+    pub width_input: String,
+    pub height_input: String,
+    pub score_input: String,
+    pub board: Board,
+    /// Rules the position will be played on with.
+    pub spawn: SpawnRules,
+    /// The cell typed digits go to.
+    selected: Option<usize>,
+    /// Digits typed into the selected cell so far, e.g. "10" on the way to 1024.
+    typed: String,
+    pub status: String,
+}
+impl Editor {
+    /// Edits `board`, with the cells outside the outline of `spawn` cut out.
+    pub fn new(
+        mut board: Board,
+        height: usize,
+        width: usize,
+        score: usize,
+        spawn: SpawnRules,
+    ) -> Editor {
+        spawn.cut_outline(&mut board, height, width);
+        Editor {
+            width,
+            height,
+            width_input: width.to_string(),
+            height_input: height.to_string(),
+            score_input: score.to_string(),
+            board,
+            spawn,
+            selected: None,
+            typed: String::new(),
+            status: String::from(
+                "Click a cell to cycle its value, right click to go back, or select it and type; # blocks it",
+            ),
+        }
+    }
+
+    fn select(&mut self, id: usize) {
+        if self.selected != Some(id) {
+            self.typed.clear();
+        }
+        self.selected = Some(id);
+    }
+
+    /// Steps the cell to the next (or previous) value, wrapping through the
+    /// blocker, the hole and the empty cell.
+    pub fn cycle(&mut self, id: usize, up: bool) {
+        self.select(id);
+        self.typed.clear();
+        let rule = self.spawn.movement.merge.rule();
+        let tile = self.board.0[id];
+        let cell = if tile.hole {
+            Cell::Hole
+        } else if tile.blocked {
+            Cell::Blocker
+        } else {
+            tile.tilecontent
+                .map_or(Cell::Empty, |content| Cell::Tile(rule.rank(content)))
+        };
+        let next = match (cell, up) {
+            (Cell::Empty, true) => Cell::Tile(1),
+            (Cell::Tile(rank), true) if rank >= CYCLE_RANKS => Cell::Blocker,
+            (Cell::Tile(rank), true) => Cell::Tile(rank + 1),
+            (Cell::Blocker, true) => Cell::Hole,
+            (Cell::Hole, true) => Cell::Empty,
+            (Cell::Empty, false) => Cell::Hole,
+            (Cell::Hole, false) => Cell::Blocker,
+            (Cell::Blocker, false) => Cell::Tile(CYCLE_RANKS),
+            (Cell::Tile(rank), false) if rank <= 1 => Cell::Empty,
+            (Cell::Tile(rank), false) => Cell::Tile(rank - 1),
+        };
+        let tile = &mut self.board.0[id];
+        tile.tilecontent = None;
+        tile.blocked = false;
+        tile.hole = false;
+        match next {
+            Cell::Empty => {}
+            Cell::Tile(rank) => tile.tilecontent = rule.nth(rank),
+            Cell::Blocker => self.board.block(id),
+            Cell::Hole => self.board.cut(id),
+        }
+    }
+
+    /// Turns the selected cell into a blocker.
+    pub fn block(&mut self) {
+        if let Some(id) = self.selected {
+            self.board.block(id);
+            self.typed.clear();
+        }
+    }
+
+    /// Adds a digit to the selected cell, which changes once the digits form a tile.
+    pub fn type_digit(&mut self, digit: char) {
+        let Some(id) = self.selected else {
+            return;
+        };
+        self.typed.push(digit);
+        let rule = self.spawn.movement.merge.rule();
+        let longest = rule.nth(CYCLE_RANKS).map_or(0, |nth| nth.to_string().len());
+        match self.typed.parse::<usize>() {
+            Ok(value) if rule.is_tile(value) => {
+                self.board.0[id].blocked = false;
+                self.board.0[id].hole = false;
+                self.board.0[id].tilecontent = Some(value);
+            }
+            // Nothing starting with these digits can become a tile.
+            _ if self.typed.len() > longest => self.typed.clear(),
+            _ => {}
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.typed.pop();
+    }
+
+    pub fn erase(&mut self) {
+        if let Some(id) = self.selected {
+            self.board.0[id].tilecontent = None;
+            self.board.0[id].blocked = false;
+            self.board.0[id].hole = false;
+            self.typed.clear();
+        }
+    }
+
+    /// Moves the selection with the arrow keys.
+    pub fn move_selection(&mut self, rows: isize, columns: isize) {
+        let (row, column) = match self.selected {
+            Some(id) => (id / self.width, id % self.width),
+            None => (0, 0),
+        };
+        let row = row.saturating_add_signed(rows).min(self.height - 1);
+        let column = column.saturating_add_signed(columns).min(self.width - 1);
+        self.select(pair_to_index(row, column, self.width));
+    }
+
+    /// Applies the size inputs, keeping the tiles, blockers and holes that still fit.
+    pub fn resize(&mut self) {
+        let parse = |input: &str| {
+            input
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|side| (2..=MAX_SIDE).contains(side))
+        };
+        let (Some(width), Some(height)) = (parse(&self.width_input), parse(&self.height_input))
+        else {
+            self.status = format!("The board sides must be between 2 and {MAX_SIDE}");
+            return;
+        };
+        // The outline follows the new size, only the holes made by hand are kept.
+        let outline = self.spawn.empty_board(self.height, self.width);
+        let old = |row: usize, column: usize| {
+            let id = pair_to_index(row, column, self.width);
+            (row < self.height && column < self.width && !outline.0[id].hole)
+                .then(|| self.board.0[id])
+        };
+        let mut board = self.spawn.empty_board(height, width);
+        for id in 0..width * height {
+            if board.0[id].hole {
+                continue;
+            }
+            match old(id / width, id % width) {
+                Some(tile) if tile.hole => board.cut(id),
+                Some(tile) if tile.blocked => board.block(id),
+                Some(tile) => board.0[id].tilecontent = tile.tilecontent,
+                None => {}
+            }
+        }
+        self.board = board;
+        self.width = width;
+        self.height = height;
+        self.selected = None;
+        self.typed.clear();
+        self.status = format!("Resized to {width}x{height}");
+    }
+
+    /// Empties every cell, keeping the shape of the board.
+    pub fn clear(&mut self) {
+        self.board.0.iter_mut().for_each(|tile| {
+            tile.tilecontent = None;
+            tile.blocked = tile.hole;
+        });
+        self.typed.clear();
+    }
+
+    /// Fills every empty cell with the value of the selected cell, or with the
+    /// smallest tile.
+    pub fn fill(&mut self) {
+        let Some(value) = self
+            .selected
+            .and_then(|id| self.board.0[id].tilecontent)
+            .or(self.spawn.movement.merge.rule().nth(1))
+        else {
+            return;
+        };
+        self.board
+            .0
+            .iter_mut()
+            .filter(|tile| tile.is_empty())
+            .for_each(|tile| tile.tilecontent = Some(value));
+    }
+
+    /// The position to hand to the game, if it can be played.
+    pub fn position(&self) -> Result<SavedGame, String> {
+        let score = self
+            .score_input
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid score: {}", self.score_input))?;
+        if self.board.0.iter().all(|tile| tile.tilecontent.is_none()) {
+            return Err(String::from("Place at least one tile"));
+        }
+        Ok(SavedGame {
+            width: self.width,
+            height: self.height,
+            score,
+            spawn: self.spawn.clone(),
+            board: self.board.clone(),
+        })
+    }
+}
+
+pub fn view(editor: &Editor) -> widget::Container<'_, Message, cosmic::Theme> {
+    let size = widget::row()
+        .push(text("Width: "))
+        .push(
+            text_input("", &editor.width_input)
+                .on_input(Message::EditorWidth)
+                .on_submit(Message::EditorResize)
+                .width(40),
+        )
+        .push(text("Height: "))
+        .push(
+            text_input("", &editor.height_input)
+                .on_input(Message::EditorHeight)
+                .on_submit(Message::EditorResize)
+                .width(40),
+        )
+        .push(button("Resize").on_press(Message::EditorResize))
+        .push(text("Score: "))
+        .push(
+            text_input("", &editor.score_input)
+                .on_input(Message::EditorScore)
+                .width(80),
+        )
+        .spacing(10)
+        .align_items(Alignment::Center);
+    let tools = widget::row()
+        .push(button("Clear").on_press(Message::EditorClear))
+        .push(button("Fill").on_press(Message::EditorFill))
+        .push(
+            button("Play from here")
+                .on_press(Message::EditorPlay)
+                .style(theme::Button::Suggested),
+        )
+        .push(
+            button("Menu")
+                .on_press(Message::CloseEditor)
+                .style(theme::Button::Destructive),
+        )
+        .spacing(20)
+        .align_items(Alignment::Center);
+    let grid = editor.spawn.movement.grid;
+    let board = board_layout(editor.height, editor.width, grid, |id| {
+        let tile = tile_view(&editor.board.0[id], editor.spawn.movement.merge);
+        let cell = if editor.selected == Some(id) {
+            widget::container(tile)
+                .style(theme::Container::custom(widget_colors::selected_cell_theme))
+                .padding(3)
+                .width(50)
+                .height(50)
+        } else {
+            tile
+        };
+        widget::mouse_area(cell)
+            .on_press(Message::EditorCycle(id, true))
+            .on_right_press(Message::EditorCycle(id, false))
+            .into()
+    });
+    widget::container(
+        widget::column()
+            .push(size)
+            .push(tools)
+            .push(text(&editor.status))
+            .push(board)
+            .spacing(20)
+            .align_items(Alignment::Center),
+    )
+    .padding(20)
+}
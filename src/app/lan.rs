@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{keyboard, subscription, Alignment, Subscription};
+use cosmic::theme;
+use cosmic::widget::{self, button, text, text_input};
+use cosmic_ext_2048::engine::spawn::SpawnRules;
+use cosmic_ext_2048::net::link::Link;
+use cosmic_ext_2048::net::{Packet, Settings, DEFAULT_PORT};
+use std::io;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use super::race::{self, Race, MINUTES, MINUTE_NAMES};
+use super::{key_to_direction, Message};
+
+/// Which side of the connection this game is on.
+#[derive(Debug, Clone)]
+pub enum Role {
+    Host { port: u16, settings: Settings },
+    Join { address: String },
+}
+
+/// A connection being made or in use. Every attempt gets a new id, which
+/// keeps its subscription apart from the ones before.
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub id: u64,
+    pub role: Role,
+}
+
+/// What the connection reports to the application.
+#[derive(Debug, Clone)]
+pub enum Event {
+    Listening(u16),
+    /// Both sides said hello, packets for the other side go into the sender.
+    Connected(UnboundedSender<Packet>),
+    Received(Packet),
+    /// The connection is gone, with the error that ended it if there was one.
+    Closed(Option<String>),
+}
+
+/// State of the network race screen.
+pub struct Lan {
+    pub address: String,
+    pub port: String,
+    /// Index into `MINUTES`, for hosting.
+    pub minutes: usize,
+    pub width: usize,
+    pub height: usize,
+    pub spawn: SpawnRules,
+    pub connection: Option<Connection>,
+    sender: Option<UnboundedSender<Packet>>,
+    pub race: Option<Race>,
+    /// Whether the other side knows that no more moves follow.
+    done_sent: bool,
+    pub status: String,
+}
+impl Lan {
+    /// Races are hosted with the size and spawn rules picked in the menu.
+    pub fn new(width: usize, height: usize, spawn: SpawnRules) -> Lan {
+        Lan {
+            address: format!("127.0.0.1:{DEFAULT_PORT}"),
+            port: DEFAULT_PORT.to_string(),
+            minutes: 1,
+            width,
+            height,
+            spawn,
+            connection: None,
+            sender: None,
+            race: None,
+            done_sent: false,
+            status: String::from("Host a race, or join one by its address"),
+        }
+    }
+
+    pub fn host(&mut self, id: u64, seed: u64) {
+        let Ok(port) = self.port.parse() else {
+            self.status = format!("Invalid port \"{}\"", self.port);
+            return;
+        };
+        let settings = Settings {
+            seed,
+            width: self.width,
+            height: self.height,
+            seconds: 60 * MINUTES[self.minutes],
+            spawn: self.spawn.clone(),
+        };
+        self.reset();
+        self.connection = Some(Connection {
+            id,
+            role: Role::Host { port, settings },
+        });
+        self.status = String::from("Opening the port…");
+    }
+
+    pub fn join(&mut self, id: u64) {
+        self.reset();
+        self.connection = Some(Connection {
+            id,
+            role: Role::Join {
+                address: self.address.clone(),
+            },
+        });
+        self.status = format!("Connecting to {}…", self.address);
+    }
+
+    /// Hangs up, telling the other side if there is one.
+    pub fn leave(&mut self) {
+        self.send(Packet::Bye);
+        self.reset();
+        self.status = String::from("Left the race");
+    }
+
+    fn reset(&mut self) {
+        self.connection = None;
+        self.sender = None;
+        self.race = None;
+        self.done_sent = false;
+    }
+
+    fn send(&mut self, packet: Packet) {
+        if let Some(sender) = &self.sender {
+            // The connection reports its own errors when it closes.
+            let _ = sender.send(packet);
+        }
+    }
+
+    fn start(&mut self, settings: &Settings) {
+        let mut race = Race::remote(settings);
+        race.start();
+        self.race = Some(race);
+        self.status = String::from("The race is on!");
+    }
+
+    pub fn event(&mut self, event: Event) {
+        match event {
+            Event::Listening(port) => {
+                self.status = format!("Waiting for a player to join on port {port}…");
+            }
+            Event::Connected(sender) => {
+                self.sender = Some(sender);
+                match self.connection.as_ref().map(|connection| &connection.role) {
+                    Some(Role::Host { settings, .. }) => {
+                        let settings = settings.clone();
+                        self.start(&settings);
+                    }
+                    _ => self.status = String::from("Connected, waiting for the host"),
+                }
+            }
+            Event::Received(Packet::Game(settings)) => self.start(&settings),
+            Event::Received(Packet::Move(direction)) => {
+                let Some(race) = &mut self.race else {
+                    return;
+                };
+                let movement = race.players[1].record.spawn.movement;
+                if movement.directions().contains(&direction) {
+                    race.move_player(1, direction);
+                } else {
+                    self.send(Packet::Bye);
+                    self.hang_up();
+                    self.status = format!(
+                        "The opponent moved {}, which this board does not allow",
+                        direction.arrow(movement.grid)
+                    );
+                }
+            }
+            Event::Received(Packet::Done) => {
+                if let Some(race) = &mut self.race {
+                    race.remote_done();
+                }
+            }
+            Event::Received(Packet::Bye) => {
+                self.hang_up();
+                self.status = String::from("The opponent left");
+            }
+            // Only sent while connecting.
+            Event::Received(Packet::Hello { .. }) => {}
+            Event::Closed(error) => {
+                if self.connection.is_none() {
+                    return;
+                }
+                self.hang_up();
+                self.status = match error {
+                    Some(error) => format!("Connection lost: {error}"),
+                    None => String::from("The opponent hung up"),
+                };
+            }
+        }
+    }
+
+    /// The arrow keys play the local board, every move goes to the other side.
+    pub fn key(&mut self, key: &keyboard::Key) {
+        let Some(race) = &mut self.race else {
+            return;
+        };
+        let grid = race.players[0].record.spawn.movement.grid;
+        let Some(direction) = key_to_direction(key, grid) else {
+            return;
+        };
+        if race.move_player(0, direction) {
+            self.send(Packet::Move(direction));
+        }
+        self.send_done();
+    }
+
+    pub fn tick(&mut self) {
+        if let Some(race) = &mut self.race {
+            race.tick();
+        }
+        self.send_done();
+    }
+
+    fn send_done(&mut self) {
+        let done = self.race.as_ref().is_some_and(|race| race.players[0].done);
+        if done && !self.done_sent {
+            self.done_sent = true;
+            self.send(Packet::Done);
+        }
+    }
+
+    /// Forgets the connection. No more moves come in, so the opponent is done.
+    fn hang_up(&mut self) {
+        self.connection = None;
+        self.sender = None;
+        if let Some(race) = &mut self.race {
+            race.remote_done();
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        self.race.as_ref().is_some_and(Race::running)
+    }
+}
+
+/// Keeps the connection of `lan` open, reporting everything that happens on it.
+pub fn subscription(lan: &Lan) -> Subscription<Message> {
+    let Some(connection) = lan.connection.clone() else {
+        return Subscription::none();
+    };
+    subscription::channel(connection.id, 100, |mut output| async move {
+        let error = run(connection, &mut output).await.err();
+        let _ = output
+            .send(Message::LanEvent(Event::Closed(
+                error.map(|error| error.to_string()),
+            )))
+            .await;
+        // Ending the subscription would only start it again.
+        std::future::pending().await
+    })
+}
+
+async fn run(connection: Connection, output: &mut mpsc::Sender<Message>) -> io::Result<()> {
+    let mut link = match &connection.role {
+        Role::Host { port, settings } => {
+            let listener = TcpListener::bind(("0.0.0.0", *port)).await?;
+            let _ = output
+                .send(Message::LanEvent(Event::Listening(*port)))
+                .await;
+            let mut link = Link::host(&listener).await?;
+            link.send(&Packet::Game(settings.clone())).await?;
+            link
+        }
+        Role::Join { address } => Link::join(address.as_str()).await?,
+    };
+    let (sender, mut outgoing) = unbounded_channel();
+    let _ = output
+        .send(Message::LanEvent(Event::Connected(sender)))
+        .await;
+    loop {
+        tokio::select! {
+            received = link.receive() => match received? {
+                Some(packet) => {
+                    let bye = packet == Packet::Bye;
+                    let _ = output.send(Message::LanEvent(Event::Received(packet))).await;
+                    if bye {
+                        return Ok(());
+                    }
+                }
+                None => return Ok(()),
+            },
+            packet = outgoing.recv() => match packet {
+                Some(packet) => link.send(&packet).await?,
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+pub fn view(lan: &Lan) -> widget::Container<'_, Message, cosmic::Theme> {
+    let idle = lan.connection.is_none();
+    let host = widget::row()
+        .push(text("Port: "))
+        .push(
+            text_input("", &lan.port)
+                .on_input(Message::LanPort)
+                .width(70),
+        )
+        .push(widget::dropdown(
+            &MINUTE_NAMES,
+            Some(lan.minutes),
+            Message::LanMinutes,
+        ))
+        .push(
+            button("Host")
+                .on_press_maybe(idle.then_some(Message::LanHost))
+                .style(theme::Button::Suggested),
+        )
+        .spacing(10)
+        .align_items(Alignment::Center);
+    let join = widget::row()
+        .push(text("Address: "))
+        .push(
+            text_input("", &lan.address)
+                .on_input(Message::LanAddress)
+                .on_submit(Message::LanJoin)
+                .width(180),
+        )
+        .push(button("Join").on_press_maybe(idle.then_some(Message::LanJoin)))
+        .spacing(10)
+        .align_items(Alignment::Center);
+    let controls = widget::row()
+        .push(host)
+        .push(join)
+        .push(button("Leave").on_press_maybe((!idle).then_some(Message::LanLeave)))
+        .push(
+            button("Menu")
+                .on_press(Message::CloseLan)
+                .style(theme::Button::Destructive),
+        )
+        .spacing(40)
+        .align_items(Alignment::Center);
+    let mut column = widget::column()
+        .push(controls)
+        .push(text(&lan.status))
+        .spacing(20)
+        .align_items(Alignment::Center);
+    if let Some(race) = &lan.race {
+        column = column
+            .push(race::clock(race))
+            .push(text(if race.players[0].done && !race.over {
+                "Waiting for the opponent to finish…"
+            } else {
+                race.status.as_str()
+            }))
+            .push(
+                widget::row()
+                    .push(race::player_view(&race.players[0], false))
+                    .push(race::player_view(&race.players[1], true))
+                    .spacing(60)
+                    .align_items(Alignment::Start),
+            );
+    }
+    widget::container(column).padding(20)
+}
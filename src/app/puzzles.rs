@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use cosmic::iced::{Alignment, Length};
+use cosmic::theme;
+use cosmic::widget::{self, button, text};
+use cosmic_ext_2048::engine::puzzle::{Puzzle, SolvedPuzzles};
+
+use super::Message;
+
+/// The puzzle selection, shown in place of the menu.
+pub fn view<'a>(
+    puzzles: &'a [Puzzle],
+    solved: &SolvedPuzzles,
+) -> widget::Container<'a, Message, cosmic::Theme> {
+    let count = puzzles
+        .iter()
+        .filter(|puzzle| solved.0.contains(&puzzle.id))
+        .count();
+    let list = puzzles
+        .iter()
+        .enumerate()
+        .fold(widget::column(), |list, (index, puzzle)| {
+            let mark = if solved.0.contains(&puzzle.id) {
+                "✓"
+            } else {
+                ""
+            };
+            list.push(
+                widget::row()
+                    .push(text(mark).size(20).width(24))
+                    .push(
+                        widget::column()
+                            .push(text(&puzzle.title).size(20))
+                            .push(text(format!(
+                                "{} on {}x{}",
+                                puzzle.task(),
+                                puzzle.width,
+                                puzzle.height
+                            )))
+                            .width(Length::Fixed(320.0)),
+                    )
+                    .push(
+                        button("Play")
+                            .on_press(Message::PlayPuzzle(index))
+                            .style(theme::Button::Suggested),
+                    )
+                    .spacing(20)
+                    .align_items(Alignment::Center),
+            )
+        });
+    widget::container(
+        widget::column()
+            .push(
+                widget::row()
+                    .push(text(format!("{count} of {} solved", puzzles.len())).size(20))
+                    .push(button("Menu").on_press(Message::ClosePuzzles))
+                    .spacing(40)
+                    .align_items(Alignment::Center),
+            )
+            .push(widget::scrollable(list.spacing(20)))
+            .spacing(20)
+            .align_items(Alignment::Center),
+    )
+    .padding(20)
+}
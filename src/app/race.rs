@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use cosmic::iced::{keyboard, Alignment};
+use cosmic::theme;
+use cosmic::widget::{self, button, text, Text};
+use cosmic::{Renderer, Theme};
+use cosmic_ext_2048::engine::board::{Board, Direction};
+use cosmic_ext_2048::engine::record::{GameRecord, RecordedMove};
+use cosmic_ext_2048::engine::spawn::SpawnRules;
+use cosmic_ext_2048::net::Settings;
+use rand::rngs::StdRng;
+use std::time::{Duration, Instant};
+
+use super::{
+    board_view, key_names, key_to_direction, letter_to_direction, mini_board_view, Message,
+};
+
+/// Race lengths offered, in minutes.
+pub const MINUTES: [u64; 4] = [1, 2, 3, 5];
+pub const MINUTE_NAMES: [&str; 4] = ["1 minute", "2 minutes", "3 minutes", "5 minutes"];
+
+/// One of the two boards of a race.
+pub struct Racer {
+    pub name: &'static str,
+    pub keys: &'static str,
+    pub record: GameRecord,
+    pub board: Board,
+    pub rng: StdRng,
+    pub score: usize,
+    pub stuck: bool,
+    /// No more moves follow, because the player is stuck or out of time.
+    pub done: bool,
+}
+impl Racer {
+    fn new(name: &'static str, keys: &'static str, record: GameRecord) -> Racer {
+        let mut rng = record.rng();
+        let board = record.start_board(&mut rng);
+        Racer {
+            name,
+            keys,
+            record,
+            board,
+            rng,
+            score: 0,
+            stuck: false,
+            done: false,
+        }
+    }
+
+    /// Returns whether the board changed.
+    pub fn make_move(&mut self, direction: Direction) -> bool {
+        if self.done {
+            return false;
+        }
+        let (height, width) = (self.record.height, self.record.width);
+        if let Some(moved) =
+            self.board
+                .move_with_rules(direction, height, width, &self.record.spawn, &mut self.rng)
+        {
+            self.score += moved.points;
+            self.record.moves.push(RecordedMove {
+                direction,
+                spawns: moved.spawned,
+            });
+            self.stuck = self
+                .board
+                .is_stuck(height, width, self.record.spawn.movement);
+            self.done = self.stuck;
+            return true;
+        }
+        false
+    }
+}
+
+/// State of a race: two players on boards dealt from the same seed.
+pub struct Race {
+    pub players: [Racer; 2],
+    /// Whether the second player is on another machine, which tells when it is done.
+    pub remote: bool,
+    pub length: Duration,
+    /// When the clock started, `None` until the race is on.
+    pub started: Option<Instant>,
+    /// Time left on the clock, updated on every tick.
+    pub remaining: Duration,
+    pub over: bool,
+    pub status: String,
+}
+impl Race {
+    pub fn new(width: usize, height: usize, spawn: SpawnRules, seed: u64, length: usize) -> Race {
+        let mut record = GameRecord::new(width, height, seed);
+        record.spawn = spawn;
+        let (arrows, letters) = key_names(record.spawn.movement.grid);
+        Race {
+            players: [
+                Racer::new("Player one", letters, record.clone()),
+                Racer::new("Player two", arrows, record),
+            ],
+            remote: false,
+            length: Duration::from_secs(60 * MINUTES[length]),
+            started: None,
+            remaining: Duration::from_secs(60 * MINUTES[length]),
+            over: false,
+            status: String::from("Press Start when both players are ready"),
+        }
+    }
+
+    /// The same race again, on a new seed.
+    pub fn restart(&self, seed: u64) -> Race {
+        let record = &self.players[0].record;
+        let mut race = Race::new(record.width, record.height, record.spawn.clone(), seed, 0);
+        race.length = self.length;
+        race.remaining = self.length;
+        race
+    }
+
+    /// A race against a player on another machine, who gets the second board.
+    pub fn remote(settings: &Settings) -> Race {
+        let mut record = GameRecord::new(settings.width, settings.height, settings.seed);
+        record.spawn = settings.spawn.clone();
+        let (arrows, _) = key_names(record.spawn.movement.grid);
+        Race {
+            players: [
+                Racer::new("You", arrows, record.clone()),
+                Racer::new("Opponent", "over the network", record),
+            ],
+            remote: true,
+            length: Duration::from_secs(settings.seconds),
+            started: None,
+            remaining: Duration::from_secs(settings.seconds),
+            over: false,
+            status: String::new(),
+        }
+    }
+
+    /// The entry of `MINUTES` the race lasts, if any.
+    pub fn minutes(&self) -> Option<usize> {
+        MINUTES
+            .iter()
+            .position(|&minutes| Duration::from_secs(60 * minutes) == self.length)
+    }
+
+    pub fn set_minutes(&mut self, index: usize) {
+        if self.started.is_none() {
+            self.length = Duration::from_secs(60 * MINUTES[index]);
+            self.remaining = self.length;
+        }
+    }
+
+    pub fn start(&mut self) {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+            self.status = String::new();
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        self.started.is_some() && !self.over
+    }
+
+    /// WASD moves the left board, the arrow keys the right one. On hex boards
+    /// the left board moves with QWE/ASD and the right one with the numpad.
+    pub fn key(&mut self, key: &keyboard::Key) {
+        if !self.running() {
+            return;
+        }
+        let grid = self.players[0].record.spawn.movement.grid;
+        if let Some(direction) = letter_to_direction(key, grid) {
+            self.move_player(0, direction);
+        } else if let Some(direction) = key_to_direction(key, grid) {
+            self.move_player(1, direction);
+        }
+    }
+
+    /// Moves one of the boards, returning whether it changed.
+    pub fn move_player(&mut self, player: usize, direction: Direction) -> bool {
+        if !self.running() {
+            return false;
+        }
+        let moved = self.players[player].make_move(direction);
+        self.finish_if_done();
+        moved
+    }
+
+    /// Marks the remote player done, once all its moves came in.
+    pub fn remote_done(&mut self) {
+        self.players[1].done = true;
+        self.finish_if_done();
+    }
+
+    /// Winds the clock down, ending the race when it runs out. A remote player
+    /// runs its own clock and says when it is done.
+    pub fn tick(&mut self) {
+        let Some(started) = self.started.filter(|_| !self.over) else {
+            return;
+        };
+        self.remaining = self.length.saturating_sub(started.elapsed());
+        if self.remaining.is_zero() {
+            self.players[0].done = true;
+            if !self.remote {
+                self.players[1].done = true;
+            }
+            self.finish_if_done();
+        }
+    }
+
+    fn finish_if_done(&mut self) {
+        if self.over || !self.players.iter().all(|player| player.done) {
+            return;
+        }
+        self.over = true;
+        let reason = if self.players.iter().all(|player| player.stuck) {
+            "Both players are stuck"
+        } else {
+            "Time is up"
+        };
+        let [one, two] = &self.players;
+        self.status = match one.score.cmp(&two.score) {
+            std::cmp::Ordering::Greater => format!("{reason}, {} wins!", one.name),
+            std::cmp::Ordering::Less => format!("{reason}, {} wins!", two.name),
+            std::cmp::Ordering::Equal => format!("{reason}, it is a draw"),
+        };
+    }
+
+    /// Writes both games to the records directory, for settling disputes.
+    pub fn store_records(&mut self) {
+        let stored: Result<Vec<_>, _> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(index, player)| {
+                let mut record = player.record.clone();
+                record.score = Some(player.score);
+                record.board = Some(player.board.clone());
+                record.store_as(&format!("race-player{}", index + 1))
+            })
+            .collect();
+        self.status = match stored {
+            Ok(paths) => format!(
+                "Records written to {}",
+                paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<String>>()
+                    .join(" and ")
+            ),
+            Err(error) => format!("Could not write the records: {error}"),
+        };
+    }
+}
+
+/// A player's name, score and board, drawn small for a remote opponent.
+pub fn player_view(player: &Racer, mini: bool) -> widget::Column<'_, Message, cosmic::Theme> {
+    let (height, width) = (player.record.height, player.record.width);
+    let movement = player.record.spawn.movement;
+    let state = match (player.stuck, player.done) {
+        (true, _) => "Stuck",
+        (false, true) => "Done",
+        (false, false) => "",
+    };
+    widget::column()
+        .push(text(format!("{} ({})", player.name, player.keys)).size(20))
+        .push(text(format!("Score: {}", player.score)).size(20))
+        .push(text(state))
+        .push(if mini {
+            mini_board_view(&player.board, height, width, movement)
+        } else {
+            board_view(&player.board, height, width, movement)
+        })
+        .spacing(10)
+        .align_items(Alignment::Center)
+}
+
+/// The time left, as minutes and seconds.
+pub fn clock(race: &Race) -> Text<Theme, Renderer> {
+    let seconds = race.remaining.as_secs();
+    text(format!("{}:{:02}", seconds / 60, seconds % 60)).size(32)
+}
+
+pub fn view(race: &Race) -> widget::Container<'_, Message, cosmic::Theme> {
+    let controls = widget::row()
+        .push(widget::dropdown(
+            &MINUTE_NAMES,
+            race.minutes(),
+            Message::RaceLength,
+        ))
+        .push(
+            button("Start")
+                .on_press_maybe(race.started.is_none().then_some(Message::RaceStart))
+                .style(theme::Button::Suggested),
+        )
+        .push(button("Restart").on_press(Message::RaceRestart))
+        .push(button("Save records").on_press_maybe(race.over.then_some(Message::RaceSaveRecords)))
+        .push(
+            button("Menu")
+                .on_press(Message::CloseRace)
+                .style(theme::Button::Destructive),
+        )
+        .spacing(20)
+        .align_items(Alignment::Center);
+    let boards = widget::row()
+        .push(player_view(&race.players[0], false))
+        .push(player_view(&race.players[1], false))
+        .spacing(60)
+        .align_items(Alignment::Start);
+    widget::container(
+        widget::column()
+            .push(controls)
+            .push(clock(race))
+            .push(text(&race.status))
+            .push(boards)
+            .spacing(20)
+            .align_items(Alignment::Center),
+    )
+    .padding(20)
+}
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use cosmic::iced::Alignment;
+use cosmic::theme;
+use cosmic::widget::{self, button, text, text_input};
+use cosmic_ext_2048::engine::record::{GameRecord, Position};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{board_view, widget_colors, Message};
+
+/// Most bars drawn by the score graph, longer games are sampled.
+const GRAPH_BARS: usize = 120;
+const GRAPH_HEIGHT: f32 = 120.0;
+/// How many of the newest records are offered for loading.
+const RECENT_RECORDS: usize = 8;
+
+/// State of the replay viewer.
+pub struct Replay {
+    pub path: String,
+    pub record: Option<GameRecord>,
+    /// The start position followed by the position after every move.
+    pub positions: Vec<Position>,
+    pub current: usize,
+    pub playing: bool,
+    /// Pause between two moves while playing, in milliseconds.
+    pub speed: u32,
+    pub status: String,
+    /// Whether exported GIFs show the score above the board.
+    pub gif_caption: bool,
+    recent: Vec<PathBuf>,
+}
+impl Replay {
+    pub fn new() -> Replay {
+        let mut recent: Vec<(PathBuf, std::time::SystemTime)> = GameRecord::records_dir()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                Some((entry.path(), entry.metadata().ok()?.modified().ok()?))
+            })
+            .collect();
+        recent.sort_by(|a, b| b.1.cmp(&a.1));
+        Replay {
+            path: String::new(),
+            record: None,
+            positions: vec![],
+            current: 0,
+            playing: false,
+            speed: 300,
+            status: String::new(),
+            gif_caption: true,
+            recent: recent
+                .into_iter()
+                .take(RECENT_RECORDS)
+                .map(|(path, _)| path)
+                .collect(),
+        }
+    }
+
+    pub fn load(&mut self, path: String) {
+        self.playing = false;
+        match GameRecord::load(Path::new(&path)) {
+            Ok(record) => {
+                self.positions = record.positions();
+                self.status = if self.positions.len() == record.moves.len() + 1 {
+                    format!("{} moves", record.moves.len())
+                } else {
+                    format!(
+                        "Move {} does not change the board, the replay stops there",
+                        self.positions.len()
+                    )
+                };
+                self.record = Some(record);
+                self.current = 0;
+            }
+            Err(error) => self.status = format!("Could not load {path}: {error}"),
+        }
+        self.path = path;
+    }
+
+    /// Replays the record strictly from its seed and reports the outcome.
+    pub fn verify(&mut self) {
+        let Some(record) = &self.record else {
+            return;
+        };
+        self.status = match record.verify() {
+            Ok(position) => format!(
+                "Verified: the seed reproduces the final score of {}",
+                position.score
+            ),
+            Err(divergence) => {
+                // Jump to the position right before things went wrong.
+                self.seek(divergence.move_number.saturating_sub(1));
+                format!("Verification failed at {divergence}")
+            }
+        };
+    }
+
+    pub fn last(&self) -> usize {
+        self.positions.len().saturating_sub(1)
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        self.current = index.min(self.last());
+    }
+
+    pub fn forward(&mut self) {
+        self.seek(self.current + 1);
+        if self.current == self.last() {
+            self.playing = false;
+        }
+    }
+
+    pub fn backward(&mut self) {
+        self.seek(self.current.saturating_sub(1));
+    }
+}
+
+/// Bar chart of the score over the whole game, the part already shown highlighted.
+fn score_graph(replay: &Replay) -> widget::Row<'_, Message, cosmic::Theme> {
+    let last = replay.last();
+    let max_score = replay
+        .positions
+        .last()
+        .map_or(0, |position| position.score)
+        .max(1);
+    let bars = (last + 1).min(GRAPH_BARS);
+    (0..bars)
+        .map(|bar| if bars > 1 { bar * last / (bars - 1) } else { 0 })
+        .fold(widget::row(), |row, index| {
+            let score = replay.positions[index].score;
+            let height = (score as f32 / max_score as f32 * GRAPH_HEIGHT).max(1.0);
+            let style = if index <= replay.current {
+                widget_colors::orange2theme
+            } else {
+                widget_colors::gray1theme
+            };
+            row.push(
+                widget::container("")
+                    .style(theme::Container::custom(style))
+                    .width(3)
+                    .height(height),
+            )
+        })
+        .spacing(1)
+        .height(GRAPH_HEIGHT)
+        .align_items(Alignment::End)
+}
+
+pub fn view(replay: &Replay) -> widget::Container<'_, Message, cosmic::Theme> {
+    let loader = widget::row()
+        .push(
+            text_input("Path to a game record", &replay.path)
+                .on_input(Message::ReplayPath)
+                .on_submit(Message::LoadReplay(replay.path.clone()))
+                .width(400),
+        )
+        .push(button("Load").on_press(Message::LoadReplay(replay.path.clone())))
+        .push(
+            button("Menu")
+                .on_press(Message::CloseReplay)
+                .style(theme::Button::Suggested),
+        )
+        .spacing(20)
+        .align_items(Alignment::Center);
+    let recent = replay.recent.iter().fold(widget::column(), |column, path| {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        column.push(
+            button::link(name)
+                .on_press(Message::LoadReplay(path.display().to_string()))
+                .padding(0),
+        )
+    });
+
+    let mut content = widget::column()
+        .push(loader)
+        .push(text(&replay.status))
+        .spacing(20)
+        .align_items(Alignment::Center);
+
+    let (Some(record), Some(position)) = (&replay.record, replay.positions.get(replay.current))
+    else {
+        return widget::container(content.push(recent)).padding(20);
+    };
+
+    let controls = widget::row()
+        .push(button("◀").on_press(Message::ReplayBackward))
+        .push(
+            button(if replay.playing { "Pause" } else { "Play" })
+                .on_press(Message::ReplayTogglePlay),
+        )
+        .push(button("▶").on_press(Message::ReplayForward))
+        .push(button("Verify").on_press(Message::VerifyReplay))
+        .push(text("Delay: "))
+        .push(widget::slider(50..=2000, replay.speed, Message::ReplaySpeed).width(150))
+        .push(text(format!("{} ms", replay.speed)))
+        .spacing(20)
+        .align_items(Alignment::Center);
+    let export = widget::row()
+        .push(button("Export GIF").on_press(Message::ExportGif))
+        .push(widget::checkbox(
+            "Score caption",
+            replay.gif_caption,
+            Message::ReplayGifCaption,
+        ))
+        .spacing(20)
+        .align_items(Alignment::Center);
+    let timeline = widget::row()
+        .push(
+            widget::slider(0..=replay.last() as u32, replay.current as u32, |index| {
+                Message::ReplaySeek(index as usize)
+            })
+            .width(400),
+        )
+        .push(text(format!("{} / {}", replay.current, replay.last())))
+        .spacing(20)
+        .align_items(Alignment::Center);
+
+    content = content
+        .push(controls)
+        .push(timeline)
+        .push(export)
+        .push(text(format!("Score: {}", position.score)).size(20))
+        .push(
+            widget::row()
+                .push(board_view(
+                    &position.board,
+                    record.height,
+                    record.width,
+                    record.spawn.movement,
+                ))
+                .push(score_graph(replay))
+                .spacing(40)
+                .align_items(Alignment::Center),
+        );
+    widget::container(content).padding(20)
+}
@@ -3,83 +3,31 @@ use cosmic::{
     iced::{Border, Color},
     iced_core::Shadow,
 };
+use cosmic_ext_2048::export::{Rgb, BLOCKER, DARK_TEXT, TILE_PALETTE};
+
+/// Turns a colour shared with the exported pictures into an iced one.
+const fn color(Rgb(r, g, b): Rgb) -> Color {
+    Color {
+        r: r as f32 / 255.0,
+        g: g as f32 / 255.0,
+        b: b as f32 / 255.0,
+        a: 1.0,
+    }
+}
 
-const GREY1RGB: Color = Color {
-    r: 238.0 / 255.0,
-    g: 228.0 / 255.0,
-    b: 218.0 / 255.0,
-    a: 1.0,
-};
-
-const GREY2RGB: Color = Color {
-    r: 237.0 / 255.0,
-    g: 224.0 / 255.0,
-    b: 200.0 / 255.0,
-    a: 1.0,
-};
-
-const ORANGE1RGB: Color = Color {
-    r: 242.0 / 255.0,
-    g: 177.0 / 255.0,
-    b: 121.0 / 255.0,
-    a: 1.0,
-};
-
-const ORANGE2RGB: Color = Color {
-    r: 245.0 / 255.0,
-    g: 149.0 / 255.0,
-    b: 99.0 / 255.0,
-    a: 1.0,
-};
-
-const RED1RGB: Color = Color {
-    r: 246.0 / 255.0,
-    g: 124.0 / 255.0,
-    b: 96.0 / 255.0,
-    a: 1.0,
-};
-
-const RED2RGB: Color = Color {
-    r: 246.0 / 255.0,
-    g: 94.0 / 255.0,
-    b: 59.0 / 255.0,
-    a: 1.0,
-};
-
-const YELLOW1RGB: Color = Color {
-    r: 237.0 / 255.0,
-    g: 207.0 / 255.0,
-    b: 115.0 / 255.0,
-    a: 1.0,
-};
-
-const YELLOW2RGB: Color = Color {
-    r: 237.0 / 255.0,
-    g: 204.0 / 255.0,
-    b: 98.0 / 255.0,
-    a: 1.0,
-};
-
-const YELLOW3RGB: Color = Color {
-    r: 237.0 / 255.0,
-    g: 200.0 / 255.0,
-    b: 80.0 / 255.0,
-    a: 1.0,
-};
-
-const YELLOW4RGB: Color = Color {
-    r: 237.0 / 255.0,
-    g: 197.0 / 255.0,
-    b: 63.0 / 255.0,
-    a: 1.0,
-};
-
-const YELLOW5RGB: Color = Color {
-    r: 237.0 / 255.0,
-    g: 194.0 / 255.0,
-    b: 45.0 / 255.0,
-    a: 1.0,
-};
+const GREY1RGB: Color = color(TILE_PALETTE[0]);
+const GREY2RGB: Color = color(TILE_PALETTE[1]);
+const ORANGE1RGB: Color = color(TILE_PALETTE[2]);
+const ORANGE2RGB: Color = color(TILE_PALETTE[3]);
+const RED1RGB: Color = color(TILE_PALETTE[4]);
+const RED2RGB: Color = color(TILE_PALETTE[5]);
+const YELLOW1RGB: Color = color(TILE_PALETTE[6]);
+const YELLOW2RGB: Color = color(TILE_PALETTE[7]);
+const YELLOW3RGB: Color = color(TILE_PALETTE[8]);
+const YELLOW4RGB: Color = color(TILE_PALETTE[9]);
+const YELLOW5RGB: Color = color(TILE_PALETTE[10]);
+const BLOCKERRGB: Color = color(BLOCKER);
+const DARK_TEXT_RGB: Color = color(DARK_TEXT);
 
 pub fn blacktheme(theme: &Theme) -> widget::container::Appearance {
     let mut appearance = orange1theme(theme);
@@ -127,12 +75,7 @@ pub fn orange2theme(theme: &Theme) -> widget::container::Appearance {
 pub fn gray1theme(theme: &Theme) -> widget::container::Appearance {
     let mut appearance = orange1theme(theme);
     appearance.background = Some(cosmic::iced::Background::Color(GREY1RGB));
-    appearance.text_color = Some(Color {
-        r: 119.0 / 255.0,
-        g: 110.0 / 255.0,
-        b: 101.0 / 255.0,
-        a: 1.0,
-    });
+    appearance.text_color = Some(DARK_TEXT_RGB);
     appearance
 }
 
@@ -182,4 +125,33 @@ pub fn yellow5theme(theme: &Theme) -> widget::container::Appearance {
     let mut appearance = orange1theme(theme);
     appearance.background = Some(cosmic::iced::Background::Color(YELLOW5RGB));
     appearance
-}
\ No newline at end of file
+}
+pub fn blocker_theme(theme: &Theme) -> widget::container::Appearance {
+    let mut appearance = secondary_with_rounder_corners(theme);
+    appearance.background = Some(cosmic::iced::Background::Color(BLOCKERRGB));
+    appearance
+}
+
+pub fn hole_theme(_theme: &Theme) -> widget::container::Appearance {
+    widget::container::Appearance {
+        background: Some(cosmic::iced::Background::Color(Color::TRANSPARENT)),
+        ..Default::default()
+    }
+}
+
+pub fn hint_arrow_theme(theme: &Theme) -> widget::container::Appearance {
+    let mut appearance = orange1theme(theme);
+    appearance.background = Some(cosmic::iced::Background::Color(Color::TRANSPARENT));
+    appearance.text_color = Some(RED2RGB);
+    appearance
+}
+
+pub fn selected_cell_theme(theme: &Theme) -> widget::container::Appearance {
+    let mut appearance = hint_arrow_theme(theme);
+    appearance.border = Border {
+        color: RED2RGB,
+        width: 3.0,
+        radius: theme.cosmic().corner_radii.radius_xs.into(),
+    };
+    appearance
+}
@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Plays games without a window and prints aggregate statistics.
+//!
+//! ```sh
+//! cosmic-ext-2048-sim --games 100 --strategy expectimax --width 4 --height 4 --seed 7 --format json
+//! ```
+//!
+//! With `--verify RECORD` it instead replays a game record from its seed and
+//! reports the first move that does not match.
+use cosmic_ext_2048::engine::ai::Strategy;
+use cosmic_ext_2048::engine::record::GameRecord;
+use cosmic_ext_2048::engine::spawn::SpawnRules;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+const USAGE: &str =
+    "usage: cosmic-ext-2048-sim [--games N] [--strategy random|greedy|expectimax|corner]
+                           [--width W] [--height H] [--seed S] [--budget-ms MS] [--format text|json]
+                           [--spawn RULES]
+       cosmic-ext-2048-sim --verify RECORD";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+struct Options {
+    games: usize,
+    strategy: Strategy,
+    width: usize,
+    height: usize,
+    seed: u64,
+    spawn: SpawnRules,
+    format: Format,
+    verify: Option<PathBuf>,
+}
+
+struct GameResult {
+    score: usize,
+    max_tile: usize,
+    moves: usize,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        games: 10,
+        strategy: Strategy::Corner,
+        width: 4,
+        height: 4,
+        seed: 0,
+        spawn: SpawnRules::classic(),
+        format: Format::Text,
+        verify: None,
+    };
+    let mut strategy = String::from("corner");
+    let mut budget = Duration::from_millis(20);
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            return Err(String::new());
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("invalid value for {flag}: {value}"))
+        };
+        match flag.as_str() {
+            "--games" => options.games = number()? as usize,
+            "--width" => options.width = number()? as usize,
+            "--height" => options.height = number()? as usize,
+            "--seed" => options.seed = number()?,
+            "--budget-ms" => budget = Duration::from_millis(number()?),
+            "--strategy" => strategy = value,
+            "--verify" => options.verify = Some(PathBuf::from(value)),
+            "--spawn" => {
+                options.spawn = SpawnRules::parse(&value)
+                    .map_err(|error| format!("invalid spawn rules: {error}"))?
+            }
+            "--format" => {
+                options.format = match value.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    _ => return Err(format!("unknown format: {value}")),
+                }
+            }
+            _ => return Err(format!("unknown option: {flag}")),
+        }
+    }
+    options.strategy = match strategy.as_str() {
+        "random" => Strategy::Random,
+        "greedy" => Strategy::Greedy,
+        "expectimax" => Strategy::Expectimax(budget),
+        "corner" => Strategy::Corner,
+        _ => return Err(format!("unknown strategy: {strategy}")),
+    };
+    if options.width < 2 || options.height < 2 {
+        return Err(String::from("the board must be at least 2x2"));
+    }
+    Ok(options)
+}
+
+fn play(options: &Options, seed: u64) -> GameResult {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (height, width) = (options.height, options.width);
+    let mut board = options.spawn.start_board(height, width, &mut rng);
+    let mut result = GameResult {
+        score: 0,
+        max_tile: 0,
+        moves: 0,
+    };
+    while let Some(direction) =
+        options
+            .strategy
+            .choose(&board, height, width, &options.spawn, &mut rng)
+    {
+        match board.move_with_rules(direction, height, width, &options.spawn, &mut rng) {
+            Some(moved) => result.score += moved.points,
+            None => break,
+        }
+        result.moves += 1;
+    }
+    result.max_tile = board.max_tile();
+    result
+}
+
+fn verify(path: &Path) -> ExitCode {
+    let record = match GameRecord::load(path) {
+        Ok(record) => record,
+        Err(error) => {
+            eprintln!("error: {}: {error}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    match record.verify() {
+        Ok(position) => {
+            println!(
+                "{}: valid, {} moves, score {}, max tile {}",
+                path.display(),
+                record.moves.len(),
+                position.score,
+                position.board.max_tile()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(divergence) => {
+            println!("{}: invalid, {divergence}", path.display());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(error) => {
+            if !error.is_empty() {
+                eprintln!("error: {error}");
+            }
+            eprintln!("{USAGE}");
+            return if error.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            };
+        }
+    };
+
+    if let Some(path) = &options.verify {
+        return verify(path);
+    }
+
+    let results: Vec<GameResult> = (0..options.games as u64)
+        .map(|game| play(&options, options.seed.wrapping_add(game)))
+        .collect();
+
+    let games = results.len().max(1) as f64;
+    let wins = results
+        .iter()
+        .filter(|result| result.max_tile >= 2048)
+        .count();
+    let win_rate = wins as f64 / games;
+    let average_score = results.iter().map(|result| result.score).sum::<usize>() as f64 / games;
+    let average_moves = results.iter().map(|result| result.moves).sum::<usize>() as f64 / games;
+    let best_score = results.iter().map(|result| result.score).max().unwrap_or(0);
+    let mut max_tiles: BTreeMap<usize, usize> = BTreeMap::new();
+    results
+        .iter()
+        .for_each(|result| *max_tiles.entry(result.max_tile).or_default() += 1);
+
+    match options.format {
+        Format::Text => {
+            println!("strategy:      {}", options.strategy.name());
+            println!("board:         {}x{}", options.width, options.height);
+            println!("mode:          {}", options.spawn.mode_name());
+            println!("seed:          {}", options.seed);
+            println!("games:         {}", results.len());
+            println!("win rate:      {:.1}%", win_rate * 100.0);
+            println!("average score: {average_score:.1}");
+            println!("best score:    {best_score}");
+            println!("average moves: {average_moves:.1}");
+            println!("max tiles:");
+            max_tiles.iter().rev().for_each(|(tile, count)| {
+                println!(
+                    "  {tile:>6}: {count:>5} ({:.1}%)",
+                    *count as f64 / games * 100.0
+                );
+            });
+        }
+        Format::Json => {
+            let distribution = max_tiles
+                .iter()
+                .map(|(tile, count)| format!("\"{tile}\":{count}"))
+                .collect::<Vec<String>>()
+                .join(",");
+            println!(
+                "{{\"strategy\":\"{}\",\"mode\":\"{}\",\"width\":{},\"height\":{},\"seed\":{},\"games\":{},\
+                 \"win_rate\":{win_rate},\"average_score\":{average_score},\"best_score\":{best_score},\
+                 \"average_moves\":{average_moves},\"max_tiles\":{{{distribution}}}}}",
+                options.strategy.name(),
+                options.spawn.mode_name(),
+                options.width,
+                options.height,
+                options.seed,
+                results.len(),
+            );
+        }
+    }
+    ExitCode::SUCCESS
+}
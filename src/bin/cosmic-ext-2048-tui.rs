@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Terminal front-end, sharing the engine and the save file with the COSMIC app.
+//!
+//! Keys: arrows move (the numpad keys around 5 on hex boards), `h` hint,
+//! `p` autoplay, `u` undo, `s` save, `l` load, `r` reset, `q` quit. Pass `--resume` to continue the saved game, or
+//! `--spawn RULES` (e.g. `--spawn "2:1 4:1 each=2"`) to change the spawn rules.
+use cosmic_ext_2048::engine::ai;
+use cosmic_ext_2048::engine::board::{pair_to_index, Board, Direction, Grid, Movement, Tile};
+use cosmic_ext_2048::engine::merge::Merge;
+use cosmic_ext_2048::engine::save::SavedGame;
+use cosmic_ext_2048::engine::spawn::SpawnRules;
+use cosmic_ext_2048::export::{tile_colors, Rgb};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::{cursor, execute, terminal};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::io::{self, Write};
+use std::time::Duration;
+
+const HINT_BUDGET: Duration = Duration::from_millis(300);
+const AUTOPLAY_BUDGET: Duration = Duration::from_millis(100);
+const AUTOPLAY_DELAY: Duration = Duration::from_millis(250);
+
+struct Game {
+    width: usize,
+    height: usize,
+    board: Board,
+    old_board: Board,
+    score: usize,
+    old_score: usize,
+    spawn: SpawnRules,
+    /// Kept between moves so the next tile can be previewed.
+    rng: StdRng,
+    hint: Option<Direction>,
+    hints_used: usize,
+    autoplay: bool,
+    status: String,
+}
+impl Game {
+    fn new(width: usize, height: usize, spawn: SpawnRules) -> Game {
+        let mut rng = StdRng::from_entropy();
+        let board = spawn.start_board(height, width, &mut rng);
+        Game {
+            width,
+            height,
+            old_board: board.clone(),
+            board,
+            score: 0,
+            old_score: 0,
+            spawn,
+            rng,
+            hint: None,
+            hints_used: 0,
+            autoplay: false,
+            status: String::new(),
+        }
+    }
+
+    fn from_saved(saved: SavedGame) -> Game {
+        Game {
+            width: saved.width,
+            height: saved.height,
+            old_board: saved.board.clone(),
+            board: saved.board,
+            score: saved.score,
+            old_score: saved.score,
+            spawn: saved.spawn,
+            rng: StdRng::from_entropy(),
+            hint: None,
+            hints_used: 0,
+            autoplay: false,
+            status: String::from("Game loaded"),
+        }
+    }
+
+    fn make_move(&mut self, direction: Direction) {
+        let old_board = self.board.clone();
+        if let Some(moved) = self.board.move_with_rules(
+            direction,
+            self.height,
+            self.width,
+            &self.spawn,
+            &mut self.rng,
+        ) {
+            self.old_board = old_board;
+            self.old_score = self.score;
+            self.score += moved.points;
+            self.hint = None;
+        }
+    }
+
+    fn render(&self, out: &mut impl Write) -> io::Result<()> {
+        execute!(
+            out,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        let next = self
+            .spawn
+            .next_value(&self.rng)
+            .map_or(String::new(), |value| format!("  next: {value}"));
+        write!(
+            out,
+            "2048  score: {}  hints used: {}{next}\r\n\r\n",
+            self.score, self.hints_used
+        )?;
+        let Movement { grid, merge, .. } = self.spawn.movement;
+        // Every cell takes two lines. Hex boards drop each column one line,
+        // half a cell, below the one on its left.
+        let drop = |w: usize| if grid == Grid::Hex { w } else { 0 };
+        for line in 0..2 * self.height + drop(self.width - 1) {
+            for w in 0..self.width {
+                match line.checked_sub(drop(w)) {
+                    Some(at) if at % 2 == 0 && at / 2 < self.height => write_tile(
+                        out,
+                        self.board.0[pair_to_index(at / 2, w, self.width)],
+                        merge,
+                    )?,
+                    _ => write!(out, "{:7}", "")?,
+                }
+            }
+            write!(out, "\r\n")?;
+        }
+        let hint = self.hint.map_or(String::new(), |direction| {
+            format!("hint: {}", direction.arrow(grid))
+        });
+        write!(out, "{hint}\r\n")?;
+        if self.autoplay {
+            write!(
+                out,
+                "autoplay on, press p to pause or an arrow to take over\r\n"
+            )?;
+        }
+        write!(out, "{}\r\n", self.status)?;
+        let keys = match grid {
+            Grid::Square => "arrows move",
+            Grid::Hex => "numpad 7 8 9 1 2 3 move",
+        };
+        write!(
+            out,
+            "{keys}  h hint  p autoplay  u undo  s save  l load  r reset  q quit\r\n"
+        )?;
+        out.flush()
+    }
+}
+
+fn write_tile(out: &mut impl Write, tile: Tile, merge: Merge) -> io::Result<()> {
+    match tile.tilecontent {
+        _ if tile.hole => write!(out, "{:7}", ""),
+        _ if tile.blocked => write!(out, "\x1b[48;2;94;84;76m{:^6}\x1b[0m ", "##"),
+        Some(content) => {
+            let (Rgb(br, bg, bb), Rgb(fr, fg, fb)) = tile_colors(merge.rule().rank(content));
+            write!(
+                out,
+                "\x1b[48;2;{br};{bg};{bb}m\x1b[38;2;{fr};{fg};{fb}m{content:^6}\x1b[0m "
+            )
+        }
+        None => write!(out, "\x1b[48;2;60;60;60m{:6}\x1b[0m ", ""),
+    }
+}
+
+/// The arrow keys move the tiles, and on hex boards the numpad keys around 5
+/// with up and down.
+fn key_to_direction(code: KeyCode, grid: Grid) -> Option<Direction> {
+    match (code, grid) {
+        (KeyCode::Up | KeyCode::Char('8'), _) => Some(Direction::Up),
+        (KeyCode::Down | KeyCode::Char('2'), _) => Some(Direction::Down),
+        (KeyCode::Left, Grid::Square) | (KeyCode::Char('7'), Grid::Hex) => Some(Direction::Left),
+        (KeyCode::Right, Grid::Square) | (KeyCode::Char('3'), Grid::Hex) => Some(Direction::Right),
+        (KeyCode::Char('9'), Grid::Hex) => Some(Direction::UpRight),
+        (KeyCode::Char('1'), Grid::Hex) => Some(Direction::DownLeft),
+        _ => None,
+    }
+}
+
+fn run(game: &mut Game, out: &mut impl Write) -> io::Result<()> {
+    loop {
+        game.render(out)?;
+        if game.autoplay && !event::poll(AUTOPLAY_DELAY)? {
+            match ai::best_direction_with(
+                &game.board,
+                game.height,
+                game.width,
+                &game.spawn,
+                AUTOPLAY_BUDGET,
+            ) {
+                Some(direction) => game.make_move(direction),
+                None => game.autoplay = false,
+            }
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if let Some(direction) = key_to_direction(key.code, game.spawn.movement.grid) {
+            game.autoplay = false;
+            game.make_move(direction);
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('h') => {
+                game.hints_used += 1;
+                game.hint = ai::best_direction_with(
+                    &game.board,
+                    game.height,
+                    game.width,
+                    &game.spawn,
+                    HINT_BUDGET,
+                );
+            }
+            KeyCode::Char('p') => game.autoplay = !game.autoplay,
+            KeyCode::Char('u') => {
+                game.board = game.old_board.clone();
+                game.score = game.old_score;
+                game.hint = None;
+            }
+            KeyCode::Char('s') => {
+                let saved = SavedGame {
+                    width: game.width,
+                    height: game.height,
+                    score: game.score,
+                    spawn: game.spawn.clone(),
+                    board: game.board.clone(),
+                };
+                game.status = match saved.store() {
+                    Ok(()) => String::from("Game saved"),
+                    Err(error) => format!("Could not save the game: {error}"),
+                };
+            }
+            KeyCode::Char('l') => match SavedGame::load() {
+                Ok(saved) => *game = Game::from_saved(saved),
+                Err(error) => game.status = format!("Could not load the game: {error}"),
+            },
+            KeyCode::Char('r') => *game = Game::new(game.width, game.height, game.spawn.clone()),
+            _ => {}
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let mut game = if args.iter().any(|arg| arg == "--resume") {
+        Game::from_saved(SavedGame::load()?)
+    } else {
+        let spawn = match args.iter().position(|arg| arg == "--spawn") {
+            Some(index) => SpawnRules::parse(args.get(index + 1).map_or("", String::as_str))
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?,
+            None => SpawnRules::classic(),
+        };
+        Game::new(4, 4, spawn)
+    };
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = run(&mut game, &mut out);
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
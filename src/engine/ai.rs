@@ -0,0 +1,402 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+use super::board::{pair_to_index, Board, Direction, Movement};
+use super::spawn::{Region, SpawnRules};
+
+/// What the search expects to appear after every move.
+struct Chance {
+    /// Probability of each value.
+    odds: Vec<(usize, f64)>,
+    region: Region,
+    movement: Movement,
+}
+
+/// Picks the direction with the best expected outcome, searching as deep as
+/// `budget` allows. Returns `None` if no direction changes the board.
+pub fn best_direction(
+    board: &Board,
+    height: usize,
+    width: usize,
+    budget: Duration,
+) -> Option<Direction> {
+    best_direction_with(board, height, width, &SpawnRules::classic(), budget)
+}
+
+/// Like `best_direction`, for a game dealt with `rules`. Only the first of
+/// several tiles spawned per move is searched, which keeps the tree small.
+pub fn best_direction_with(
+    board: &Board,
+    height: usize,
+    width: usize,
+    rules: &SpawnRules,
+    budget: Duration,
+) -> Option<Direction> {
+    let deadline = Instant::now() + budget;
+    let spawns = Chance {
+        odds: rules.odds(),
+        region: rules.region,
+        movement: rules.movement,
+    };
+    let mut best = None;
+    let mut depth = 1;
+    // Iterative deepening: keep the answer of the deepest search that finished in time.
+    loop {
+        let mut found: Option<(Direction, f64)> = None;
+        for &direction in spawns.movement.directions() {
+            let mut moved = board.clone();
+            if moved
+                .slide(direction, height, width, spawns.movement)
+                .is_none()
+            {
+                continue;
+            }
+            let Some(value) = chance_node(&moved, height, width, depth, &spawns, deadline) else {
+                return best.or(found.map(|(direction, _)| direction));
+            };
+            if found.is_none_or(|(_, best_value)| value > best_value) {
+                found = Some((direction, value));
+            }
+        }
+        match found {
+            Some((direction, _)) => best = Some(direction),
+            None => return None,
+        }
+        if Instant::now() >= deadline || depth >= 8 {
+            return best;
+        }
+        depth += 1;
+    }
+}
+
+fn max_node(
+    board: &Board,
+    height: usize,
+    width: usize,
+    depth: usize,
+    spawns: &Chance,
+    deadline: Instant,
+) -> Option<f64> {
+    if depth == 0 {
+        return Some(evaluate(board, height, width));
+    }
+    if Instant::now() >= deadline {
+        return None;
+    }
+    let mut best: Option<f64> = None;
+    for &direction in spawns.movement.directions() {
+        let mut moved = board.clone();
+        if moved
+            .slide(direction, height, width, spawns.movement)
+            .is_some()
+        {
+            let value = chance_node(&moved, height, width, depth, spawns, deadline)?;
+            best = Some(best.map_or(value, |best| best.max(value)));
+        }
+    }
+    // A board with no legal move is a lost game.
+    Some(best.unwrap_or(-1.0e9))
+}
+
+fn chance_node(
+    board: &Board,
+    height: usize,
+    width: usize,
+    depth: usize,
+    spawns: &Chance,
+    deadline: Instant,
+) -> Option<f64> {
+    let empty_ids: Vec<usize> = board
+        .0
+        .iter()
+        .filter(|tile| tile.is_empty())
+        .map(|tile| tile.id)
+        .filter(|&id| spawns.region.contains(id, height, width))
+        .collect();
+    if empty_ids.is_empty() {
+        return max_node(board, height, width, depth - 1, spawns, deadline);
+    }
+    let mut total = 0.0;
+    for &id in &empty_ids {
+        for &(value, chance) in &spawns.odds {
+            let mut spawned = board.clone();
+            spawned.0[id].tilecontent = Some(value);
+            total += chance * max_node(&spawned, height, width, depth - 1, spawns, deadline)?;
+        }
+    }
+    Some(total / empty_ids.len() as f64)
+}
+
+/// Heuristic value of a position: plenty of empty tiles, rows and columns
+/// sorted in one direction and the biggest tile sitting in a corner.
+pub(crate) fn evaluate(board: &Board, height: usize, width: usize) -> f64 {
+    let value_at = |h: usize, w: usize| {
+        board.0[pair_to_index(h, w, width)]
+            .tilecontent
+            .map_or(0.0, |content| (content as f64).log2())
+    };
+    let empty = board.0.iter().filter(|tile| tile.is_empty()).count() as f64;
+
+    let monotonicity = |line: Vec<f64>| {
+        let (mut increasing, mut decreasing) = (0.0, 0.0);
+        line.windows(2).for_each(|pair| {
+            if pair[0] > pair[1] {
+                decreasing += pair[0] - pair[1];
+            } else {
+                increasing += pair[1] - pair[0];
+            }
+        });
+        -f64::min(increasing, decreasing)
+    };
+    let rows: f64 = (0..height)
+        .map(|h| monotonicity((0..width).map(|w| value_at(h, w)).collect()))
+        .sum();
+    let columns: f64 = (0..width)
+        .map(|w| monotonicity((0..height).map(|h| value_at(h, w)).collect()))
+        .sum();
+
+    let max = (0..height)
+        .flat_map(|h| (0..width).map(move |w| (h, w)))
+        .map(|(h, w)| value_at(h, w))
+        .fold(0.0, f64::max);
+    let corner = [
+        (0, 0),
+        (0, width - 1),
+        (height - 1, 0),
+        (height - 1, width - 1),
+    ]
+    .iter()
+    .any(|&(h, w)| value_at(h, w) == max);
+
+    empty * 2.7 + rows + columns + if corner { max } else { 0.0 }
+}
+
+/// The ways a computer player can pick its moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Any direction that changes the board.
+    Random,
+    /// The direction scoring the most points right away.
+    Greedy,
+    /// `best_direction` with the given search budget.
+    Expectimax(Duration),
+    /// Down, then left, then right, keeping big tiles in the bottom left corner.
+    /// Hex boards fall back on the diagonal last.
+    Corner,
+}
+impl Strategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Random => "random",
+            Strategy::Greedy => "greedy",
+            Strategy::Expectimax(_) => "expectimax",
+            Strategy::Corner => "corner",
+        }
+    }
+
+    pub fn choose(
+        &self,
+        board: &Board,
+        height: usize,
+        width: usize,
+        rules: &SpawnRules,
+        rng: &mut impl Rng,
+    ) -> Option<Direction> {
+        let legal = rules.movement.directions().iter().filter_map(|&direction| {
+            let mut moved = board.clone();
+            let points = moved.slide(direction, height, width, rules.movement)?;
+            Some((direction, points, moved))
+        });
+        match self {
+            Strategy::Random => legal.map(|(direction, _, _)| direction).choose(rng),
+            Strategy::Greedy => legal
+                .max_by_key(|(_, points, moved)| {
+                    let empty = moved.0.iter().filter(|tile| tile.is_empty());
+                    (*points, empty.count())
+                })
+                .map(|(direction, _, _)| direction),
+            Strategy::Expectimax(budget) => {
+                best_direction_with(board, height, width, rules, *budget)
+            }
+            Strategy::Corner => {
+                let legal: Vec<Direction> = legal.map(|(direction, _, _)| direction).collect();
+                [
+                    Direction::Down,
+                    Direction::Left,
+                    Direction::Right,
+                    Direction::Up,
+                    Direction::DownLeft,
+                    Direction::UpRight,
+                ]
+                .into_iter()
+                .find(|direction| legal.contains(direction))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const ALL: [Strategy; 4] = [
+        Strategy::Random,
+        Strategy::Greedy,
+        Strategy::Expectimax(Duration::from_millis(5)),
+        Strategy::Corner,
+    ];
+
+    /// Only moves left and right change it.
+    fn sideways() -> Board {
+        Board::from_contents(vec![Some(2), None, Some(4), Some(8), None, Some(16)])
+    }
+
+    #[test]
+    fn stuck_boards_have_no_move() {
+        let stuck = Board::from_contents(vec![Some(2), Some(4), Some(4), Some(2)]);
+        let rules = SpawnRules::classic();
+        assert_eq!(best_direction(&stuck, 2, 2, Duration::from_millis(5)), None);
+        for strategy in ALL {
+            let mut rng = StdRng::seed_from_u64(0);
+            assert_eq!(strategy.choose(&stuck, 2, 2, &rules, &mut rng), None);
+        }
+    }
+
+    #[test]
+    fn only_legal_moves_are_chosen() {
+        let board = sideways();
+        let rules = SpawnRules::classic();
+        for strategy in ALL {
+            for seed in 0..10 {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let direction = strategy.choose(&board, 2, 3, &rules, &mut rng).unwrap();
+                assert!(
+                    board
+                        .clone()
+                        .slide(direction, 2, 3, rules.movement)
+                        .is_some(),
+                    "{strategy:?} chose {direction:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn choices_are_the_same_for_the_same_seed() {
+        let board = sideways();
+        let rules = SpawnRules::classic();
+        // A zero budget always searches one round, the same one every time.
+        let strategies = [
+            Strategy::Random,
+            Strategy::Greedy,
+            Strategy::Expectimax(Duration::ZERO),
+            Strategy::Corner,
+        ];
+        for strategy in strategies {
+            let choose =
+                |seed| strategy.choose(&board, 2, 3, &rules, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(choose(7), choose(7), "{strategy:?}");
+        }
+    }
+
+    #[test]
+    fn no_budget_still_moves() {
+        let board = Board::from_contents(vec![
+            Some(2),
+            Some(4),
+            Some(8),
+            Some(16),
+            None,
+            None,
+            Some(2),
+            Some(2),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(4),
+        ]);
+        let direction = best_direction(&board, 4, 4, Duration::ZERO).unwrap();
+        assert!(board
+            .clone()
+            .slide(direction, 4, 4, Movement::default())
+            .is_some());
+    }
+
+    #[test]
+    fn autoplay_runs_until_the_game_is_over() {
+        let rules = SpawnRules::classic();
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut board = rules.start_board(4, 4, &mut rng);
+        let mut moves = 0;
+        while let Some(direction) = best_direction_with(&board, 4, 4, &rules, Duration::ZERO) {
+            assert!(board
+                .move_with_rules(direction, 4, 4, &rules, &mut rng)
+                .is_some());
+            moves += 1;
+            assert!(moves < 10_000, "the game should end on a 4x4 board");
+        }
+        assert!(board.is_stuck(4, 4, rules.movement));
+        assert!(board.max_tile() >= 256, "reached {}", board.max_tile());
+    }
+
+    /// Plays a whole game the way the simulator does, returning the final
+    /// board and score.
+    fn simulated(strategy: Strategy, seed: u64) -> (Board, usize) {
+        let rules = SpawnRules::classic();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = rules.start_board(4, 4, &mut rng);
+        let mut score = 0;
+        while let Some(direction) = strategy.choose(&board, 4, 4, &rules, &mut rng) {
+            score += board
+                .move_with_rules(direction, 4, 4, &rules, &mut rng)
+                .unwrap()
+                .points;
+        }
+        (board, score)
+    }
+
+    #[test]
+    fn simulated_games_repeat_for_the_same_seed() {
+        for strategy in [
+            Strategy::Random,
+            Strategy::Greedy,
+            Strategy::Expectimax(Duration::ZERO),
+            Strategy::Corner,
+        ] {
+            assert_eq!(
+                simulated(strategy, 11),
+                simulated(strategy, 11),
+                "{strategy:?}"
+            );
+            assert_ne!(
+                simulated(strategy, 11),
+                simulated(strategy, 12),
+                "{strategy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn corner_prefers_down_then_left() {
+        let rules = SpawnRules::classic();
+        let mut rng = StdRng::seed_from_u64(0);
+        let open = Board::from_contents(vec![Some(2), None, None, None]);
+        assert_eq!(
+            Strategy::Corner.choose(&open, 2, 2, &rules, &mut rng),
+            Some(Direction::Down)
+        );
+        let bottom_right = Board::from_contents(vec![None, None, None, Some(2)]);
+        assert_eq!(
+            Strategy::Corner.choose(&bottom_right, 2, 2, &rules, &mut rng),
+            Some(Direction::Left)
+        );
+    }
+}
@@ -0,0 +1,857 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use rand::Rng;
+
+use super::merge::{Merge, MergeRule};
+use super::spawn::SpawnRules;
+
+pub fn pair_to_index(i: usize, j: usize, width: usize) -> usize {
+    i * width + j
+}
+
+/// One of the directions the tiles can be slid in. The diagonal is only
+/// played on hex boards, see `Grid::Hex`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// One row up and one column right.
+    UpRight,
+    /// One row down and one column left.
+    DownLeft,
+}
+impl Direction {
+    /// The directions of a square board.
+    pub const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Up,
+        Direction::Down,
+    ];
+    /// The directions of a hex board.
+    pub const HEX: [Direction; 6] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Up,
+        Direction::Down,
+        Direction::UpRight,
+        Direction::DownLeft,
+    ];
+
+    /// The letter used for this direction in game records.
+    pub fn letter(self) -> char {
+        match self {
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+            Direction::Up => 'U',
+            Direction::Down => 'D',
+            Direction::UpRight => 'F',
+            Direction::DownLeft => 'B',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Direction> {
+        Direction::HEX
+            .into_iter()
+            .find(|direction| direction.letter() == letter)
+    }
+
+    /// The arrow showing which way the tiles go on screen. Hex boards are drawn
+    /// with every column half a cell lower than the one on its left, which
+    /// turns the rows into diagonals.
+    pub fn arrow(self, grid: Grid) -> &'static str {
+        match (self, grid) {
+            (Direction::Left, Grid::Square) => "←",
+            (Direction::Left, Grid::Hex) => "↖",
+            (Direction::Right, Grid::Square) => "→",
+            (Direction::Right, Grid::Hex) => "↘",
+            (Direction::Up, _) => "↑",
+            (Direction::Down, _) => "↓",
+            (Direction::UpRight, _) => "↗",
+            (Direction::DownLeft, _) => "↙",
+        }
+    }
+}
+
+/// The cells a board is made of.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Grid {
+    #[default]
+    Square,
+    /// Flat-topped hexagons, kept in the rows and columns of a rectangle: a
+    /// cell touches the ones left and right of it, above and below it, and
+    /// diagonally up-right and down-left. The other two corners of the
+    /// rectangle are cut off, leaving a hexagon.
+    Hex,
+}
+impl Grid {
+    pub const ALL: [Grid; 2] = [Grid::Square, Grid::Hex];
+
+    /// The name used in spawn rules, e.g. `grid=hex`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Grid::Square => "square",
+            Grid::Hex => "hex",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Grid> {
+        Grid::ALL.into_iter().find(|grid| grid.name() == name)
+    }
+
+    /// The name shown in the menu.
+    pub fn label(self) -> &'static str {
+        match self {
+            Grid::Square => "Squares",
+            Grid::Hex => "Hexagons",
+        }
+    }
+
+    pub fn directions(self) -> &'static [Direction] {
+        match self {
+            Grid::Square => &Direction::ALL,
+            Grid::Hex => &Direction::HEX,
+        }
+    }
+
+    /// Whether the cell `id` is part of a board of this grid.
+    pub fn contains(self, id: usize, height: usize, width: usize) -> bool {
+        let (h, w) = (id / width, id % width);
+        // Half the shorter side is cut off the top left and bottom right corners.
+        let corner = height.min(width) / 2;
+        match self {
+            Grid::Square => true,
+            Grid::Hex => h + w >= corner && (height - 1 - h) + (width - 1 - w) >= corner,
+        }
+    }
+
+    /// Cuts the cells outside the grid out of `board`.
+    pub fn cut(self, board: &mut Board, height: usize, width: usize) {
+        (0..height * width)
+            .filter(|&id| !self.contains(id, height, width))
+            .for_each(|id| board.cut(id));
+    }
+}
+
+/// How the tiles travel and merge when they are slid, the classic game by
+/// default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Movement {
+    /// Lines wrap round: a tile leaving one edge comes back in at the other.
+    pub wrap: bool,
+    /// Tiles move at most one cell per slide, as in Threes. Such lines do
+    /// not wrap.
+    pub step: bool,
+    pub grid: Grid,
+    pub merge: Merge,
+}
+impl Movement {
+    /// The directions the tiles can be slid in.
+    pub fn directions(self) -> &'static [Direction] {
+        self.grid.directions()
+    }
+}
+
+/// A tile put on the board after a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spawn {
+    pub id: usize,
+    pub value: usize,
+}
+
+/// What a successful move did to the board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Moved {
+    pub points: usize,
+    /// The tiles that appeared afterwards, in the order they were dealt.
+    pub spawned: Vec<Spawn>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board(pub Vec<Tile>);
+impl Board {
+    pub const TWO_OR_FOUR: [usize; 10] = [2, 2, 2, 2, 2, 2, 2, 2, 2, 4];
+    pub fn new(height: usize, width: usize, rng: &mut impl Rng) -> Board {
+        SpawnRules::classic().start_board(height, width, rng)
+    }
+    /// Builds a board from row-major tile contents, without spawning anything.
+    pub fn from_contents(contents: Vec<Option<usize>>) -> Board {
+        Board(
+            contents
+                .into_iter()
+                .enumerate()
+                .map(|(id, tilecontent)| Tile {
+                    tilecontent,
+                    id,
+                    blocked: false,
+                    hole: false,
+                })
+                .collect(),
+        )
+    }
+    /// Turns the cell `id` into a blocker, which tiles cannot pass or merge into.
+    pub fn block(&mut self, id: usize) {
+        self.0[id].tilecontent = None;
+        self.0[id].blocked = true;
+        self.0[id].hole = false;
+    }
+    /// Takes the cell `id` off the board, leaving a hole that stops the tiles
+    /// like a blocker.
+    pub fn cut(&mut self, id: usize) {
+        self.block(id);
+        self.0[id].hole = true;
+    }
+    /// Slides the tiles in `direction` and, if anything moved, spawns a new tile.
+    /// Returns `None` if nothing moved.
+    pub fn move_tile_content(
+        &mut self,
+        direction: Direction,
+        height: usize,
+        width: usize,
+        rng: &mut impl Rng,
+    ) -> Option<Moved> {
+        self.move_with_rules(direction, height, width, &SpawnRules::classic(), rng)
+    }
+    /// Like `move_tile_content`, spawning tiles according to `rules`.
+    pub fn move_with_rules(
+        &mut self,
+        direction: Direction,
+        height: usize,
+        width: usize,
+        rules: &SpawnRules,
+        rng: &mut impl Rng,
+    ) -> Option<Moved> {
+        let points = self.slide(direction, height, width, rules.movement)?;
+        let spawned = rules.spawn_after_move(self, height, width, rng);
+        Some(Moved { points, spawned })
+    }
+    /// Puts a tile on the empty cell `id`, for games where a player picks the
+    /// spawns instead of the rules. Returns `None` if the cell is taken or off the board.
+    pub fn place(&mut self, id: usize, value: usize) -> Option<Spawn> {
+        let tile = self.0.get_mut(id).filter(|tile| tile.is_empty())?;
+        tile.tilecontent = Some(value);
+        Some(Spawn { id, value })
+    }
+    /// Slides and merges the tiles in `direction` without spawning anything.
+    /// Returns the points scored by the merges, or `None` if nothing moved.
+    pub fn slide(
+        &mut self,
+        direction: Direction,
+        height: usize,
+        width: usize,
+        movement: Movement,
+    ) -> Option<usize> {
+        let old_board = self.clone();
+        let rule = movement.merge.rule();
+        let points = lines(direction, height, width)
+            .iter()
+            .map(|line| {
+                if movement.step {
+                    self.step_line(line, rule)
+                } else if movement.wrap {
+                    self.slide_ring(line, rule)
+                } else {
+                    self.slide_line(line, rule)
+                }
+            })
+            .sum();
+        (old_board != *self).then_some(points)
+    }
+    /// Whether no direction changes the board anymore.
+    pub fn is_stuck(&self, height: usize, width: usize, movement: Movement) -> bool {
+        movement.directions().iter().all(|&direction| {
+            self.clone()
+                .slide(direction, height, width, movement)
+                .is_none()
+        })
+    }
+    pub fn max_tile(&self) -> usize {
+        self.0
+            .iter()
+            .filter_map(|tile| tile.tilecontent)
+            .max()
+            .unwrap_or(0)
+    }
+    /// Packs the tiles on the cells of `line` towards its first cell. Blockers
+    /// stay put and split the line into segments that are packed on their own.
+    fn collapse_line(&mut self, line: &[usize]) {
+        let segments: Vec<&[usize]> = line.split(|&id| self.0[id].blocked).collect();
+        for segment in segments {
+            let contents: Vec<usize> = segment
+                .iter()
+                .filter_map(|&id| self.0[id].tilecontent)
+                .collect();
+            segment.iter().enumerate().for_each(|(i, &id)| {
+                self.0[id].tilecontent = contents.get(i).copied();
+            });
+        }
+    }
+    /// Slides the tiles of `line` towards its first cell. Neighbours merge as
+    /// `rule` says, each tile at most once and the ones nearest the front first.
+    fn slide_line(&mut self, line: &[usize], rule: &dyn MergeRule) -> usize {
+        self.collapse_line(line);
+        let mut points = 0;
+        for pair in line.windows(2) {
+            let (front, back) = (pair[0], pair[1]);
+            if let (Some(ahead), Some(behind)) =
+                (self.0[front].tilecontent, self.0[back].tilecontent)
+            {
+                if let Some(merged) = rule.merge(ahead, behind) {
+                    self.0[front].tilecontent = Some(merged);
+                    self.0[back].tilecontent = None;
+                    points += merged;
+                }
+            }
+        }
+        self.collapse_line(line);
+        points
+    }
+    /// Moves the tiles of `line` one cell towards its first cell, into an empty
+    /// cell or onto a tile they merge with. Once one tile has moved, the ones
+    /// behind it follow into the cell it left, so at most one merge happens.
+    fn step_line(&mut self, line: &[usize], rule: &dyn MergeRule) -> usize {
+        let mut points = 0;
+        for pair in line.windows(2) {
+            let (front, back) = (pair[0], pair[1]);
+            let Some(behind) = self.0[back].tilecontent else {
+                continue;
+            };
+            match self.0[front].tilecontent {
+                _ if self.0[front].blocked => continue,
+                None => self.0[front].tilecontent = Some(behind),
+                Some(ahead) => match rule.merge(ahead, behind) {
+                    Some(merged) => {
+                        self.0[front].tilecontent = Some(merged);
+                        points += merged;
+                    }
+                    None => continue,
+                },
+            }
+            self.0[back].tilecontent = None;
+        }
+        points
+    }
+    /// Slides the tiles of `line` with its ends joined, so that a tile leaving
+    /// the first cell comes back in at the last one.
+    ///
+    /// Blockers are the only walls: the line is read from the first one on, and
+    /// the stretches between blockers are slid like lines of their own. Without
+    /// a blocker nothing stops the tiles but each other, so the tile with the
+    /// most room ahead stays where it is (it would only chase the last tile round
+    /// the board) and the others close up behind it. A full line is read from
+    /// the first tile that cannot merge with the one ahead of it.
+    fn slide_ring(&mut self, line: &[usize], rule: &dyn MergeRule) -> usize {
+        let start = match line.iter().position(|&id| self.0[id].blocked) {
+            Some(blocker) => blocker + 1,
+            None => self.ring_front(line, rule),
+        };
+        let rotated: Vec<usize> = line[start..]
+            .iter()
+            .chain(&line[..start])
+            .copied()
+            .collect();
+        self.slide_line(&rotated, rule)
+    }
+
+    /// Where the tiles of a line without blockers line up from, see `slide_ring`.
+    fn ring_front(&self, line: &[usize], rule: &dyn MergeRule) -> usize {
+        let content = |at: usize| self.0[line[at]].tilecontent;
+        let length = line.len();
+        let tiles: Vec<usize> = (0..length).filter(|&at| content(at).is_some()).collect();
+        if tiles.len() == length {
+            let value = |at: usize| content(at).unwrap_or(0);
+            return (0..length)
+                .find(|&at| {
+                    rule.merge(value((at + length - 1) % length), value(at))
+                        .is_none()
+                })
+                .unwrap_or(0);
+        }
+        let mut front = 0;
+        let mut most_room = 0;
+        for (i, &at) in tiles.iter().enumerate() {
+            let ahead = tiles[(i + tiles.len() - 1) % tiles.len()];
+            let room = (at + length - ahead - 1) % length;
+            if room > most_room {
+                (front, most_room) = (at, room);
+            }
+        }
+        front
+    }
+}
+
+/// The cells of every line the tiles travel along in `direction`, each one
+/// starting at the edge they move towards.
+fn lines(direction: Direction, height: usize, width: usize) -> Vec<Vec<usize>> {
+    let row = |h: usize| (0..width).map(move |w| pair_to_index(h, w, width));
+    let column = |w: usize| (0..height).map(move |h| pair_to_index(h, w, width));
+    // The cells with `h + w == sum`, from the top right one down.
+    let diagonal = |sum: usize| {
+        (sum.saturating_sub(width - 1)..height.min(sum + 1))
+            .map(move |h| pair_to_index(h, sum - h, width))
+    };
+    let diagonals = 0..height + width - 1;
+    match direction {
+        Direction::Left => (0..height).map(|h| row(h).collect()).collect(),
+        Direction::Right => (0..height).map(|h| row(h).rev().collect()).collect(),
+        Direction::Up => (0..width).map(|w| column(w).collect()).collect(),
+        Direction::Down => (0..width).map(|w| column(w).rev().collect()).collect(),
+        Direction::UpRight => diagonals.map(|sum| diagonal(sum).collect()).collect(),
+        Direction::DownLeft => diagonals.map(|sum| diagonal(sum).rev().collect()).collect(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tile {
+    pub tilecontent: Option<usize>,
+    pub id: usize,
+    /// A blocker never holds a tile and splits its row and column in two.
+    pub blocked: bool,
+    /// Not part of the board at all. Holes are always blocked too.
+    pub hole: bool,
+}
+impl Tile {
+    /// Whether a tile can be put here.
+    pub fn is_empty(&self) -> bool {
+        self.tilecontent.is_none() && !self.blocked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    const WALLS: Movement = Movement {
+        wrap: false,
+        step: false,
+        grid: Grid::Square,
+        merge: Merge::Doubling,
+    };
+    const WRAP: Movement = Movement {
+        wrap: true,
+        step: false,
+        grid: Grid::Square,
+        merge: Merge::Doubling,
+    };
+    const HEX: Movement = Movement {
+        wrap: false,
+        step: false,
+        grid: Grid::Hex,
+        merge: Merge::Doubling,
+    };
+    const THREES: Movement = Movement {
+        wrap: false,
+        step: true,
+        grid: Grid::Square,
+        merge: Merge::Threes,
+    };
+    const FIBONACCI: Movement = Movement {
+        wrap: false,
+        step: false,
+        grid: Grid::Square,
+        merge: Merge::Fibonacci,
+    };
+
+    /// A board from rows of `.`, `#` for a blocker and tile values, e.g. "2 . # 4/. . . .".
+    fn board(rows: &str) -> Board {
+        let cells: Vec<&str> = rows.split('/').flat_map(str::split_whitespace).collect();
+        let mut board = Board::from_contents(cells.iter().map(|cell| cell.parse().ok()).collect());
+        for (id, cell) in cells.iter().enumerate() {
+            if *cell == "#" {
+                board.block(id);
+            }
+        }
+        board
+    }
+
+    fn rows(board: &Board, width: usize) -> String {
+        board
+            .0
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|tile| match tile.tilecontent {
+                        _ if tile.blocked => String::from("#"),
+                        Some(content) => content.to_string(),
+                        None => String::from("."),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    /// Slides a one-row board, returning the points and the row afterwards.
+    fn slide_row(row: &str, direction: Direction, movement: Movement) -> Option<(usize, String)> {
+        let mut board = board(row);
+        let width = board.0.len();
+        let points = board.slide(direction, 1, width, movement)?;
+        Some((points, rows(&board, width)))
+    }
+
+    fn random_board(height: usize, width: usize, rng: &mut impl Rng) -> Board {
+        let mut board = Board::from_contents(
+            (0..height * width)
+                .map(|_| rng.gen_bool(0.6).then(|| 1 << rng.gen_range(1..5)))
+                .collect(),
+        );
+        for id in 0..height * width {
+            if rng.gen_ratio(1, 10) {
+                board.block(id);
+            }
+        }
+        board
+    }
+
+    fn transposed(board: &Board, height: usize, width: usize) -> Board {
+        let mut transposed = board.clone();
+        for h in 0..height {
+            for w in 0..width {
+                let tile = board.0[pair_to_index(h, w, width)];
+                let id = pair_to_index(w, h, height);
+                transposed.0[id] = Tile { id, ..tile };
+            }
+        }
+        transposed
+    }
+
+    /// `board` turned half a turn, which swaps the opposite directions.
+    fn turned(board: &Board) -> Board {
+        let mut turned = board.clone();
+        turned.0.reverse();
+        turned
+            .0
+            .iter_mut()
+            .enumerate()
+            .for_each(|(id, tile)| tile.id = id);
+        turned
+    }
+
+    fn opposite(direction: Direction) -> Direction {
+        match direction {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::UpRight,
+        }
+    }
+
+    fn sum(board: &Board) -> usize {
+        board.0.iter().filter_map(|tile| tile.tilecontent).sum()
+    }
+
+    #[test]
+    fn blockers_split_lines() {
+        assert_eq!(
+            slide_row("2 2 # 2 .", Direction::Left, WALLS),
+            Some((4, String::from("4 . # 2 ."))),
+        );
+        assert_eq!(
+            slide_row("2 2 # 2 .", Direction::Right, WALLS),
+            Some((4, String::from(". 4 # . 2"))),
+        );
+        // Nothing can slide past the blocker or into it.
+        assert_eq!(slide_row(". 4 # . 2", Direction::Right, WALLS), None);
+        assert_eq!(board(". 4 # . 2").place(2, 2), None);
+    }
+
+    #[test]
+    fn tiles_cross_the_edge() {
+        assert_eq!(
+            slide_row("2 . . . 4 .", Direction::Left, WRAP),
+            Some((0, String::from(". . . . 4 2"))),
+        );
+        assert_eq!(
+            slide_row("2 . . . 2 .", Direction::Left, WRAP),
+            Some((4, String::from(". . . . 4 ."))),
+        );
+        assert_eq!(
+            slide_row(". 4 . . . 2", Direction::Right, WRAP),
+            Some((0, String::from("2 4 . . . ."))),
+        );
+    }
+
+    #[test]
+    fn tiles_merge_across_the_edge() {
+        assert_eq!(
+            slide_row("2 4 8 2", Direction::Left, WRAP),
+            Some((4, String::from(". 4 8 4"))),
+        );
+        assert_eq!(
+            slide_row("2 4 8 2", Direction::Right, WRAP),
+            Some((4, String::from("4 4 8 ."))),
+        );
+        // With walls the two 2s never meet.
+        assert_eq!(slide_row("2 4 8 2", Direction::Left, WALLS), None);
+    }
+
+    #[test]
+    fn tiles_merge_once_in_a_ring() {
+        assert_eq!(
+            slide_row("2 2 2 2", Direction::Left, WRAP),
+            Some((8, String::from("4 4 . ."))),
+        );
+        assert_eq!(
+            slide_row("4 4 8 .", Direction::Left, WRAP),
+            Some((8, String::from("8 8 . ."))),
+        );
+    }
+
+    #[test]
+    fn lone_tiles_stay_put() {
+        for at in 0..5 {
+            let mut cells = ["."; 5];
+            cells[at] = "8";
+            for direction in Direction::ALL {
+                assert_eq!(board(&cells.join(" ")).slide(direction, 1, 5, WRAP), None);
+            }
+        }
+    }
+
+    #[test]
+    fn packed_rings_stay_put() {
+        // The 4 and the 2 already touch across the edge.
+        assert_eq!(slide_row("2 . . 4", Direction::Left, WRAP), None);
+        assert_eq!(slide_row("2 . . 4", Direction::Right, WRAP), None);
+        assert_eq!(
+            slide_row("2 . . 4", Direction::Left, WALLS),
+            Some((0, String::from("2 4 . ."))),
+        );
+    }
+
+    #[test]
+    fn blockers_are_the_only_walls() {
+        // The stretch from the blocker round to the blocker again wraps the edge.
+        assert_eq!(
+            slide_row("2 # 4 . 2", Direction::Left, WRAP),
+            Some((4, String::from(". # 4 4 ."))),
+        );
+        assert_eq!(
+            slide_row("2 # 4 . 2", Direction::Right, WRAP),
+            Some((4, String::from("4 # . . 4"))),
+        );
+        assert_eq!(
+            slide_row(". 2 # 2 .", Direction::Left, WRAP),
+            Some((4, String::from(". . # 4 ."))),
+        );
+    }
+
+    #[test]
+    fn wrapping_stops_being_stuck() {
+        let full = board("2 4 2/4 8 4/2 4 2");
+        assert!(full.is_stuck(3, 3, WALLS));
+        assert!(!full.is_stuck(3, 3, WRAP));
+        let mut moved = full.clone();
+        assert_eq!(moved.slide(Direction::Left, 3, 3, WRAP), Some(16));
+        assert_eq!(rows(&moved, 3), ". 4 4/. 8 8/. 4 4");
+    }
+
+    #[test]
+    fn blockers_on_the_edge_make_wrapping_classic() {
+        let mut rng = StdRng::seed_from_u64(47);
+        for _ in 0..500 {
+            let (height, width) = (rng.gen_range(1..6), rng.gen_range(2..7));
+            let mut board = random_board(height, width, &mut rng);
+            for h in 0..height {
+                board.block(pair_to_index(h, 0, width));
+            }
+            for direction in [Direction::Left, Direction::Right] {
+                let (mut walls, mut wrap) = (board.clone(), board.clone());
+                assert_eq!(
+                    walls.slide(direction, height, width, WALLS),
+                    wrap.slide(direction, height, width, WRAP),
+                    "{}",
+                    rows(&board, width)
+                );
+                assert_eq!(walls, wrap, "{}", rows(&board, width));
+            }
+        }
+    }
+
+    #[test]
+    fn slides_keep_the_tiles() {
+        let mut rng = StdRng::seed_from_u64(2048);
+        for _ in 0..500 {
+            let (height, width) = (rng.gen_range(1..6), rng.gen_range(1..6));
+            let board = random_board(height, width, &mut rng);
+            for movement in [WALLS, WRAP] {
+                for direction in Direction::HEX {
+                    let mut moved = board.clone();
+                    let points = moved.slide(direction, height, width, movement);
+                    assert_eq!(sum(&moved), sum(&board));
+                    let blocked = |board: &Board| -> Vec<bool> {
+                        board.0.iter().map(|tile| tile.blocked).collect()
+                    };
+                    assert_eq!(blocked(&moved), blocked(&board));
+                    // Every merge scores the tile it makes and leaves one tile fewer.
+                    let count = |board: &Board| {
+                        board
+                            .0
+                            .iter()
+                            .filter(|tile| tile.tilecontent.is_some())
+                            .count()
+                    };
+                    if points.unwrap_or(0) == 0 {
+                        assert_eq!(count(&moved), count(&board));
+                    } else {
+                        assert!(count(&moved) < count(&board));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn columns_slide_like_rows() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..500 {
+            let (height, width) = (rng.gen_range(1..6), rng.gen_range(1..6));
+            let board = random_board(height, width, &mut rng);
+            for movement in [WALLS, WRAP] {
+                for (column, row) in [
+                    (Direction::Up, Direction::Left),
+                    (Direction::Down, Direction::Right),
+                ] {
+                    let mut by_column = board.clone();
+                    let points = by_column.slide(column, height, width, movement);
+                    let mut by_row = transposed(&board, height, width);
+                    assert_eq!(by_row.slide(row, width, height, movement), points);
+                    assert_eq!(transposed(&by_row, width, height), by_column);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hex_boards_are_hexagons() {
+        let mut hexagon = board(". . . . ./. . . . ./. . . . ./. . . . ./. . . . .");
+        Grid::Hex.cut(&mut hexagon, 5, 5);
+        assert_eq!(
+            rows(&hexagon, 5),
+            "# # . . ./# . . . ./. . . . ./. . . . #/. . . # #"
+        );
+        let mut hexagon = board(". . ./. . ./. . .");
+        Grid::Hex.cut(&mut hexagon, 3, 3);
+        assert_eq!(rows(&hexagon, 3), "# . ./. . ./. . #");
+    }
+
+    #[test]
+    fn tiles_slide_along_the_diagonal() {
+        let start = board("# . ./. 2 ./2 . #");
+        let mut moved = start.clone();
+        assert_eq!(moved.slide(Direction::UpRight, 3, 3, HEX), Some(4));
+        assert_eq!(rows(&moved, 3), "# . 4/. . ./. . #");
+        let mut moved = start.clone();
+        assert_eq!(moved.slide(Direction::DownLeft, 3, 3, HEX), Some(4));
+        assert_eq!(rows(&moved, 3), "# . ./. . ./4 . #");
+        // Cells down and right of each other do not touch.
+        let mut apart = board("# 2 ./. . 2/. . #");
+        assert_eq!(apart.slide(Direction::UpRight, 3, 3, HEX), None);
+    }
+
+    #[test]
+    fn hex_boards_are_stuck_later() {
+        let full = board("2 4/4 2");
+        assert!(full.is_stuck(2, 2, WALLS));
+        assert!(!full.is_stuck(2, 2, HEX));
+        assert_eq!(WALLS.directions(), Direction::ALL);
+        assert_eq!(HEX.directions(), Direction::HEX);
+    }
+
+    #[test]
+    fn opposite_directions_mirror_each_other() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..500 {
+            let (height, width) = (rng.gen_range(1..6), rng.gen_range(1..6));
+            let board = random_board(height, width, &mut rng);
+            for movement in [WALLS, WRAP] {
+                for direction in Direction::HEX {
+                    let mut moved = board.clone();
+                    let points = moved.slide(direction, height, width, movement);
+                    let mut moved_back = turned(&board);
+                    assert_eq!(
+                        moved_back.slide(opposite(direction), height, width, movement),
+                        points
+                    );
+                    assert_eq!(turned(&moved_back), moved, "{}", rows(&board, width));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fibonacci_neighbours_merge() {
+        assert_eq!(
+            slide_row("1 2 3 5", Direction::Left, FIBONACCI),
+            Some((3 + 8, String::from("3 8 . ."))),
+        );
+        assert_eq!(
+            slide_row("1 2 3 5", Direction::Right, FIBONACCI),
+            Some((3 + 8, String::from(". . 3 8"))),
+        );
+        assert_eq!(
+            slide_row("1 1 1 .", Direction::Left, FIBONACCI),
+            Some((2, String::from("2 1 . ."))),
+        );
+        assert_eq!(slide_row("2 2 5 .", Direction::Left, FIBONACCI), None);
+        assert_eq!(
+            slide_row("2 2 4 4", Direction::Left, WALLS),
+            Some((4 + 8, String::from("4 8 . ."))),
+        );
+    }
+
+    #[test]
+    fn threes_tiles_move_one_cell() {
+        let step = |row: &str| slide_row(row, Direction::Left, THREES);
+        assert_eq!(step(". 3 . 6"), Some((0, String::from("3 . 6 ."))));
+        assert_eq!(step("1 2 3 3"), Some((3, String::from("3 3 3 ."))));
+        assert_eq!(step("3 6 1 2"), Some((3, String::from("3 6 3 ."))));
+        assert_eq!(step("3 3 6 6"), Some((6, String::from("6 6 6 ."))));
+        assert_eq!(step("2 2 1 1"), Some((3, String::from("2 3 1 ."))));
+        assert_eq!(step("3 # . 3"), Some((0, String::from("3 # 3 ."))));
+        assert_eq!(step("1 1 3 6"), None);
+        assert_eq!(
+            slide_row("1 2 3 3", Direction::Right, THREES),
+            Some((6, String::from(". 1 2 6"))),
+        );
+        // Single steps work with the other merge rules too.
+        let doubling = Movement {
+            merge: Merge::Doubling,
+            ..THREES
+        };
+        assert_eq!(
+            slide_row("2 2 2 2", Direction::Left, doubling),
+            Some((4, String::from("4 2 2 ."))),
+        );
+    }
+
+    #[test]
+    fn tiles_are_only_placed_on_free_cells() {
+        let mut placed = board("2 . # ./. . . .");
+        placed.cut(3);
+        assert_eq!(placed.place(1, 4), Some(Spawn { id: 1, value: 4 }));
+        assert_eq!(placed.0[1].tilecontent, Some(4));
+        for taken in [0, 1, 2, 3, 8] {
+            let before = placed.clone();
+            assert_eq!(placed.place(taken, 2), None, "cell {taken}");
+            assert_eq!(placed, before);
+        }
+    }
+
+    #[test]
+    fn hot_seat_turns_slide_then_place() {
+        // Player one slides without a spawn, then player two picks the cell.
+        let mut turn = board("2 2 . ./. . . .");
+        assert_eq!(turn.slide(Direction::Left, 2, 4, WALLS), Some(4));
+        assert_eq!(turn, board("4 . . ./. . . ."));
+        assert!(turn.place(7, 2).is_some());
+        assert_eq!(turn, board("4 . . ./. . . 2"));
+    }
+}
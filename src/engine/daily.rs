@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::save::SavedGame;
+
+const MAGIC: &str = "cosmic-ext-2048 daily 1";
+/// Everyone plays the daily game on a classic board of this size.
+pub const SIDE: usize = 4;
+
+/// Today, as days since 1970-01-01. Days change at midnight UTC, so that
+/// everyone gets the same game whatever their time zone.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400)
+}
+
+/// The seed of the daily game, the same for everyone playing on `day`.
+pub fn seed(day: u64) -> u64 {
+    // SplitMix64, so that neighbouring days get unrelated games.
+    let mut z = day.wrapping_add(0x2048).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// `day` as a date, e.g. "2024-06-30".
+pub fn date(day: u64) -> String {
+    // Howard Hinnant's civil_from_days, for days after 1970.
+    let z = day + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + u64::from(m <= 2);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// Reads a date written by `date`.
+pub fn parse_date(input: &str) -> Option<u64> {
+    let mut parts = input.splitn(3, '-').map(|part| part.parse::<u64>().ok());
+    let (y, m, d) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) || y < 1970 {
+        return None;
+    }
+    // Howard Hinnant's days_from_civil.
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let day = (era * 146_097 + doe).checked_sub(719_468)?;
+    // Rejects days past the end of the month, like 2023-02-30.
+    (date(day) == input).then_some(day)
+}
+
+/// The scored attempt at one day's game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyResult {
+    pub day: u64,
+    pub score: usize,
+    pub max_tile: usize,
+    pub moves: usize,
+    /// Whether the game is over, an unfinished attempt keeps the score reached.
+    pub finished: bool,
+}
+
+/// Every day's result, one line each:
+///
+/// ```text
+/// cosmic-ext-2048 daily 1
+/// 2024-06-29 5120 512 402 finished
+/// 2024-06-30 812 64 95 unfinished
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DailyResults(pub Vec<DailyResult>);
+impl DailyResults {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("cosmic-ext-2048").join("daily.txt"))
+    }
+
+    /// Reads the results, none before the first daily game.
+    pub fn load() -> io::Result<DailyResults> {
+        let path = Self::default_path().ok_or(io::ErrorKind::NotFound)?;
+        match fs::read_to_string(path) {
+            Ok(input) => Self::parse(&input)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(DailyResults::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn store(&self) -> io::Result<()> {
+        let path = Self::default_path().ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())
+    }
+
+    pub fn parse(input: &str) -> Result<DailyResults, String> {
+        let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some(MAGIC) {
+            return Err(format!(
+                "not a list of daily results, expected \"{MAGIC}\" on the first line"
+            ));
+        }
+        let mut results = DailyResults::default();
+        for line in lines {
+            let result = line
+                .split(' ')
+                .collect::<Vec<&str>>()
+                .try_into()
+                .ok()
+                .and_then(|[day, score, max_tile, moves, finished]: [&str; 5]| {
+                    Some(DailyResult {
+                        day: parse_date(day)?,
+                        score: score.parse().ok()?,
+                        max_tile: max_tile.parse().ok()?,
+                        moves: moves.parse().ok()?,
+                        finished: match finished {
+                            "finished" => true,
+                            "unfinished" => false,
+                            _ => return None,
+                        },
+                    })
+                })
+                .ok_or_else(|| format!("invalid daily result: {line}"))?;
+            results.record(result);
+        }
+        Ok(results)
+    }
+
+    pub fn get(&self, day: u64) -> Option<&DailyResult> {
+        self.0.iter().find(|result| result.day == day)
+    }
+
+    /// Keeps `result`, replacing the one of the same day.
+    pub fn record(&mut self, result: DailyResult) {
+        self.0.retain(|known| known.day != result.day);
+        self.0.push(result);
+        self.0.sort_by_key(|result| result.day);
+    }
+
+    /// Days played in a row up to `today`. Today's game not being played yet
+    /// does not break the streak.
+    pub fn streak(&self, today: u64) -> usize {
+        let start = if self.get(today).is_some() {
+            today
+        } else {
+            today.saturating_sub(1)
+        };
+        (0..=start)
+            .rev()
+            .take_while(|&day| self.get(day).is_some())
+            .count()
+    }
+}
+
+impl std::fmt::Display for DailyResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{MAGIC}")?;
+        for result in &self.0 {
+            writeln!(
+                f,
+                "{} {} {} {} {}",
+                date(result.day),
+                result.score,
+                result.max_tile,
+                result.moves,
+                if result.finished {
+                    "finished"
+                } else {
+                    "unfinished"
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of a daily game, for pasting into a chat.
+pub fn share_text(result: &DailyResult, streak: usize, game: &SavedGame) -> String {
+    let days = if streak == 1 { "day" } else { "days" };
+    format!(
+        "2048 daily {}: score {}, best tile {}, {} moves\nStreak: {streak} {days}\n{}",
+        date(result.day),
+        result.score,
+        result.max_tile,
+        result.moves,
+        game.emoji_rows()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_round_trip() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(19_723), "2024-01-01");
+        assert_eq!(date(19_782), "2024-02-29");
+        for day in (0..40_000).step_by(37) {
+            assert_eq!(parse_date(&date(day)), Some(day));
+        }
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+    }
+
+    #[test]
+    fn counts_streaks() {
+        let mut results = DailyResults::default();
+        assert_eq!(results.streak(100), 0);
+        for day in [90, 97, 98, 99] {
+            results.record(DailyResult {
+                day,
+                score: 0,
+                max_tile: 0,
+                moves: 0,
+                finished: true,
+            });
+        }
+        // Today's game may still be played.
+        assert_eq!(results.streak(100), 3);
+        assert_eq!(results.streak(99), 3);
+        assert_eq!(results.streak(101), 0);
+        assert_eq!(DailyResults::parse(&results.to_string()), Ok(results));
+    }
+}
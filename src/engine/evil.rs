@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::ai::evaluate;
+use super::board::{Board, Spawn};
+use super::spawn::SpawnRules;
+
+/// Spawns searched further at every opponent turn, the worst looking ones.
+const SHORTLIST: usize = 8;
+/// Spawns scored to find the shortlist. Boards with more are scored on an even
+/// spread of their cells, so every board up to 8x8 is still scored in full.
+const SCORED: usize = 128;
+/// Largest board, in cells, that `Minimax` searches two rounds deep. Bigger
+/// ones get one round, like `Cruel`.
+const MINIMAX_CELLS: usize = 64;
+
+/// How hard the opponent dealing the tiles tries to end the game.
+///
+/// Every level is deterministic given the random number generator, so evil
+/// games replay and verify from their seed like any other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Malice {
+    /// One spawn in three is a `Cruel` one, the others are random.
+    SlightlyMean,
+    /// Two spawns in three are `Cruel` ones.
+    Mean,
+    /// Always the spawn after which the player's best reply is worst.
+    Cruel,
+    /// Searches two full rounds of spawns and replies.
+    Minimax,
+}
+impl Malice {
+    pub const ALL: [Malice; 4] = [
+        Malice::SlightlyMean,
+        Malice::Mean,
+        Malice::Cruel,
+        Malice::Minimax,
+    ];
+
+    /// The name used in spawn rules, e.g. `evil=cruel`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Malice::SlightlyMean => "slightly-mean",
+            Malice::Mean => "mean",
+            Malice::Cruel => "cruel",
+            Malice::Minimax => "minimax",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Malice> {
+        Malice::ALL.into_iter().find(|malice| malice.name() == name)
+    }
+
+    /// The name shown in the menu and the high scores.
+    pub fn label(self) -> &'static str {
+        match self {
+            Malice::SlightlyMean => "Slightly mean",
+            Malice::Mean => "Mean",
+            Malice::Cruel => "Cruel",
+            Malice::Minimax => "Full minimax",
+        }
+    }
+
+    /// How many spawns out of three are picked on purpose.
+    fn thirds(self) -> u32 {
+        match self {
+            Malice::SlightlyMean => 1,
+            Malice::Mean => 2,
+            Malice::Cruel | Malice::Minimax => 3,
+        }
+    }
+
+    /// Rounds of player replies searched after the spawn on a board of `cells`.
+    fn depth(self, cells: usize) -> usize {
+        match self {
+            Malice::Minimax if cells <= MINIMAX_CELLS => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// The tiles the rules allow to appear on `board` that look worst for the player.
+fn candidates(board: &Board, height: usize, width: usize, rules: &SpawnRules) -> Vec<Spawn> {
+    let ids = spawn_ids(board, height, width, rules);
+    let scored_ids = (SCORED / rules.weights.len()).max(1);
+    let step = ids.len().div_ceil(scored_ids).max(1);
+    let mut scratch = board.clone();
+    let mut candidates: Vec<(Spawn, f64)> = ids
+        .into_iter()
+        .step_by(step)
+        .flat_map(|id| {
+            rules
+                .weights
+                .iter()
+                .map(move |&(value, _)| Spawn { id, value })
+        })
+        .map(|spawn| {
+            scratch.0[spawn.id].tilecontent = Some(spawn.value);
+            let value = evaluate(&scratch, height, width);
+            scratch.0[spawn.id].tilecontent = None;
+            (spawn, value)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+    candidates
+        .into_iter()
+        .take(SHORTLIST)
+        .map(|(spawn, _)| spawn)
+        .collect()
+}
+
+/// The cells the rules allow a tile to appear on.
+fn spawn_ids(board: &Board, height: usize, width: usize, rules: &SpawnRules) -> Vec<usize> {
+    board
+        .0
+        .iter()
+        .filter(|tile| tile.is_empty())
+        .map(|tile| tile.id)
+        .filter(|&id| rules.region.contains(id, height, width))
+        .collect()
+}
+
+/// Every tile the rules allow to appear on `board`.
+fn all_spawns(board: &Board, height: usize, width: usize, rules: &SpawnRules) -> Vec<Spawn> {
+    spawn_ids(board, height, width, rules)
+        .into_iter()
+        .flat_map(|id| {
+            rules
+                .weights
+                .iter()
+                .map(move |&(value, _)| Spawn { id, value })
+        })
+        .collect()
+}
+
+fn spawned(board: &Board, spawn: Spawn) -> Board {
+    let mut board = board.clone();
+    board.0[spawn.id].tilecontent = Some(spawn.value);
+    board
+}
+
+/// The value of `board` for the player to move, looking `depth` rounds ahead.
+fn player_value(
+    board: &Board,
+    height: usize,
+    width: usize,
+    rules: &SpawnRules,
+    depth: usize,
+) -> f64 {
+    if depth == 0 {
+        return evaluate(board, height, width);
+    }
+    let mut best: Option<f64> = None;
+    for &direction in rules.movement.directions() {
+        let mut moved = board.clone();
+        if moved
+            .slide(direction, height, width, rules.movement)
+            .is_some()
+        {
+            let value = opponent_value(&moved, height, width, rules, depth);
+            best = Some(best.map_or(value, |best| best.max(value)));
+        }
+    }
+    // Nothing is worse for the player than a lost game.
+    best.unwrap_or(-1.0e9)
+}
+
+/// The value of `board` once the opponent has spawned its worst tile.
+fn opponent_value(
+    board: &Board,
+    height: usize,
+    width: usize,
+    rules: &SpawnRules,
+    depth: usize,
+) -> f64 {
+    candidates(board, height, width, rules)
+        .into_iter()
+        .map(|spawn| player_value(&spawned(board, spawn), height, width, rules, depth - 1))
+        .reduce(f64::min)
+        .unwrap_or_else(|| player_value(board, height, width, rules, depth - 1))
+}
+
+/// Picks the spawn that hurts the player most. Returns `None` if there is no
+/// room for one.
+pub fn worst_spawn(
+    board: &Board,
+    height: usize,
+    width: usize,
+    rules: &SpawnRules,
+    malice: Malice,
+    rng: &mut impl Rng,
+) -> Option<Spawn> {
+    if !rng.gen_ratio(malice.thirds(), 3) {
+        // Every cell comes once per value, so this is a fair pick of the cell.
+        let id = all_spawns(board, height, width, rules).choose(rng)?.id;
+        return Some(Spawn {
+            id,
+            value: rules.pick_value(rng),
+        });
+    }
+    let depth = malice.depth(height * width);
+    let mut worst: Option<(Spawn, f64)> = None;
+    for spawn in candidates(board, height, width, rules) {
+        let value = player_value(&spawned(board, spawn), height, width, rules, depth);
+        if worst.is_none_or(|(_, worst_value)| value < worst_value) {
+            worst = Some((spawn, value));
+        }
+    }
+    worst.map(|(spawn, _)| spawn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::board::Direction;
+    use crate::engine::save::MAX_SIDE;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::time::{Duration, Instant};
+
+    fn rules(malice: Malice) -> SpawnRules {
+        SpawnRules {
+            opponent: Some(malice),
+            ..SpawnRules::parse("2:9 4:1 blockers=2 shape=donut").unwrap()
+        }
+    }
+
+    /// A board some moves into a game of `rules`.
+    fn played(rules: &SpawnRules, side: usize, moves: usize) -> Board {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut board = rules.start_board(side, side, &mut rng);
+        let tame = SpawnRules {
+            opponent: None,
+            ..rules.clone()
+        };
+        for &direction in Direction::ALL.iter().cycle().take(moves) {
+            board.move_with_rules(direction, side, side, &tame, &mut rng);
+        }
+        board
+    }
+
+    #[test]
+    fn spawns_legal_tiles_on_free_cells() {
+        for malice in Malice::ALL {
+            let rules = rules(malice);
+            let board = played(&rules, 5, 12);
+            let mut rng = StdRng::seed_from_u64(1);
+            for _ in 0..10 {
+                let spawn = worst_spawn(&board, 5, 5, &rules, malice, &mut rng).unwrap();
+                let tile = board.0[spawn.id];
+                assert!(tile.is_empty() && !tile.blocked && !tile.hole, "{malice:?}");
+                assert!(rules.weights.iter().any(|&(value, _)| value == spawn.value));
+            }
+        }
+    }
+
+    #[test]
+    fn spawns_the_same_for_the_same_seed() {
+        for malice in Malice::ALL {
+            let rules = rules(malice);
+            let board = played(&rules, 5, 12);
+            let spawn = |seed| {
+                worst_spawn(
+                    &board,
+                    5,
+                    5,
+                    &rules,
+                    malice,
+                    &mut StdRng::seed_from_u64(seed),
+                )
+            };
+            assert_eq!(spawn(9), spawn(9), "{malice:?}");
+        }
+    }
+
+    #[test]
+    fn cruel_spawns_hurt_the_player() {
+        let rules = SpawnRules {
+            opponent: Some(Malice::Cruel),
+            ..SpawnRules::classic()
+        };
+        let board = Board::from_contents(
+            [
+                [Some(2), Some(4), Some(8), Some(16)],
+                [None, None, Some(4), Some(64)],
+                [None, None, None, Some(128)],
+                [None, None, None, Some(256)],
+            ]
+            .concat(),
+        );
+        let mut rng = StdRng::seed_from_u64(0);
+        let spawn = worst_spawn(&board, 4, 4, &rules, Malice::Cruel, &mut rng).unwrap();
+        let value = |spawn| player_value(&spawned(&board, spawn), 4, 4, &rules, 1);
+        let chosen = value(spawn);
+        let others: Vec<f64> = all_spawns(&board, 4, 4, &rules)
+            .into_iter()
+            .map(value)
+            .collect();
+        assert!(others.iter().any(|&other| other > chosen));
+        let average = others.iter().sum::<f64>() / others.len() as f64;
+        assert!(chosen < average);
+    }
+
+    #[test]
+    fn largest_boards_deal_quickly() {
+        let rules = SpawnRules {
+            opponent: Some(Malice::Minimax),
+            ..SpawnRules::classic()
+        };
+        let board = played(&rules, MAX_SIDE, 400);
+        let started = Instant::now();
+        let mut rng = StdRng::seed_from_u64(3);
+        assert!(worst_spawn(
+            &board,
+            MAX_SIDE,
+            MAX_SIDE,
+            &rules,
+            Malice::Minimax,
+            &mut rng
+        )
+        .is_some());
+        // Generous for unoptimised test builds, a release build takes a fraction of it.
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "{:?}",
+            started.elapsed()
+        );
+    }
+}
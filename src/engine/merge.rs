@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+/// Decides which neighbouring tiles merge and what they make. Sliding only
+/// asks the rule, so a new variant is one more implementation of it.
+pub trait MergeRule {
+    /// What the tile `behind` makes when it slides into the tile `ahead`, if
+    /// the two merge. The new tile is also the points scored for the merge.
+    fn merge(&self, ahead: usize, behind: usize) -> Option<usize>;
+
+    /// The tile of the given rank, counting from 1 for the smallest, or `None`
+    /// once it no longer fits in a `usize`.
+    fn nth(&self, rank: usize) -> Option<usize>;
+
+    /// The rank of `value`, which is what picks the colour of its tile. Values
+    /// past the largest tile get the rank after it.
+    fn rank(&self, value: usize) -> usize {
+        (1..)
+            .find(|&rank| self.nth(rank).is_none_or(|nth| nth >= value))
+            .unwrap_or(0)
+    }
+
+    /// Whether `value` can be a tile at all.
+    fn is_tile(&self, value: usize) -> bool {
+        value > 0 && self.nth(self.rank(value)) == Some(value)
+    }
+}
+
+/// The classic rule: two equal tiles make one twice as big.
+pub struct Doubling;
+impl MergeRule for Doubling {
+    fn merge(&self, ahead: usize, behind: usize) -> Option<usize> {
+        (ahead == behind).then_some(ahead * 2)
+    }
+
+    fn nth(&self, rank: usize) -> Option<usize> {
+        1usize.checked_shl(u32::try_from(rank).ok()?)
+    }
+}
+
+/// Neighbouring Fibonacci numbers add up to the next one: 1 and 1 make 2,
+/// 1 and 2 make 3, 2 and 3 make 5 and so on.
+pub struct Fibonacci;
+impl MergeRule for Fibonacci {
+    fn merge(&self, ahead: usize, behind: usize) -> Option<usize> {
+        let (small, big) = (ahead.min(behind), ahead.max(behind));
+        let neighbours = (small == 1 && big == 1)
+            || (self.is_tile(small) && self.nth(self.rank(small) + 1) == Some(big));
+        neighbours.then_some(small + big)
+    }
+
+    fn nth(&self, rank: usize) -> Option<usize> {
+        let (mut previous, mut value) = (1usize, 1usize);
+        for _ in 0..rank.saturating_sub(1) {
+            (previous, value) = (value, previous.checked_add(value)?);
+        }
+        Some(value)
+    }
+}
+
+/// As in Threes: a 1 and a 2 make 3, and from 3 up equal tiles double.
+pub struct Threes;
+impl MergeRule for Threes {
+    fn merge(&self, ahead: usize, behind: usize) -> Option<usize> {
+        match (ahead, behind) {
+            (1, 2) | (2, 1) => Some(3),
+            _ if ahead == behind && ahead >= 3 => Some(ahead * 2),
+            _ => None,
+        }
+    }
+
+    fn nth(&self, rank: usize) -> Option<usize> {
+        match rank {
+            0..=2 => Some(rank),
+            _ => 1usize
+                .checked_shl(u32::try_from(rank - 3).ok()?)?
+                .checked_mul(3),
+        }
+    }
+}
+
+/// The merge rules a game can be played with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Merge {
+    #[default]
+    Doubling,
+    Fibonacci,
+    Threes,
+}
+impl Merge {
+    pub const ALL: [Merge; 3] = [Merge::Doubling, Merge::Fibonacci, Merge::Threes];
+
+    /// The name used in spawn rules, e.g. `merge=fibonacci`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Merge::Doubling => "doubling",
+            Merge::Fibonacci => "fibonacci",
+            Merge::Threes => "threes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Merge> {
+        Merge::ALL.into_iter().find(|merge| merge.name() == name)
+    }
+
+    /// The name shown in the menu.
+    pub fn label(self) -> &'static str {
+        match self {
+            Merge::Doubling => "Doubling",
+            Merge::Fibonacci => "Fibonacci",
+            Merge::Threes => "Threes",
+        }
+    }
+
+    pub fn rule(self) -> &'static dyn MergeRule {
+        match self {
+            Merge::Doubling => &Doubling,
+            Merge::Fibonacci => &Fibonacci,
+            Merge::Threes => &Threes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::save::SavedGame;
+    use crate::engine::spawn::SpawnRules;
+
+    #[test]
+    fn doubling() {
+        assert_eq!(Doubling.merge(8, 8), Some(16));
+        assert_eq!(Doubling.merge(8, 4), None);
+        assert_eq!(
+            (1..=4)
+                .flat_map(|rank| Doubling.nth(rank))
+                .collect::<Vec<_>>(),
+            [2, 4, 8, 16]
+        );
+        assert_eq!(Doubling.rank(2048), 11);
+        assert!(Doubling.is_tile(1024));
+        assert!(!Doubling.is_tile(1) && !Doubling.is_tile(12));
+    }
+
+    #[test]
+    fn fibonacci() {
+        assert_eq!(
+            (1..=8)
+                .flat_map(|rank| Fibonacci.nth(rank))
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 5, 8, 13, 21, 34]
+        );
+        assert_eq!(Fibonacci.merge(1, 1), Some(2));
+        assert_eq!(Fibonacci.merge(1, 2), Some(3));
+        assert_eq!(Fibonacci.merge(3, 2), Some(5));
+        assert_eq!(Fibonacci.merge(13, 21), Some(34));
+        // Equal tiles only merge as the two 1s at the start of the sequence.
+        assert_eq!(Fibonacci.merge(2, 2), None);
+        assert_eq!(Fibonacci.merge(3, 8), None);
+        assert_eq!(Fibonacci.merge(4, 6), None);
+        assert_eq!(Fibonacci.rank(21), 7);
+        assert!(Fibonacci.is_tile(1) && Fibonacci.is_tile(89));
+        assert!(!Fibonacci.is_tile(4) && !Fibonacci.is_tile(0));
+    }
+
+    #[test]
+    fn threes() {
+        assert_eq!(
+            (1..=7)
+                .flat_map(|rank| Threes.nth(rank))
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 6, 12, 24, 48]
+        );
+        assert_eq!(Threes.merge(1, 2), Some(3));
+        assert_eq!(Threes.merge(2, 1), Some(3));
+        assert_eq!(Threes.merge(3, 3), Some(6));
+        assert_eq!(Threes.merge(48, 48), Some(96));
+        assert_eq!(Threes.merge(1, 1), None);
+        assert_eq!(Threes.merge(2, 2), None);
+        assert_eq!(Threes.merge(3, 6), None);
+        assert_eq!(Threes.merge(2, 3), None);
+        assert!(Threes.is_tile(1) && Threes.is_tile(2) && Threes.is_tile(384));
+        assert!(!Threes.is_tile(4) && !Threes.is_tile(9));
+    }
+
+    #[test]
+    fn huge_values_are_not_tiles() {
+        for merge in Merge::ALL {
+            let rule = merge.rule();
+            assert!(!rule.is_tile(usize::MAX), "{}", merge.name());
+            assert!(rule.rank(usize::MAX) > rule.rank(usize::MAX / 4));
+        }
+        assert!(Doubling.is_tile(1 << 63));
+        assert_eq!(Doubling.nth(64), None);
+        assert_eq!(Threes.nth(66), None);
+        assert!(SavedGame::from_clipboard("2048:2x2:0:18446744073709551615,./.,.").is_err());
+    }
+
+    #[test]
+    fn spawn_rules_only_deal_tiles() {
+        let rules = SpawnRules::parse("1:9 2:1 merge=fibonacci").unwrap();
+        assert_eq!(rules.movement.merge, Merge::Fibonacci);
+        assert_eq!(SpawnRules::parse(&rules.to_string()), Ok(rules));
+        assert!(SpawnRules::parse("1:9 2:1").is_err());
+        assert!(SpawnRules::parse("2:1 4:1 merge=fibonacci").is_err());
+        assert!(SpawnRules::parse("2:1 merge=tripling").is_err());
+    }
+}
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+pub mod ai;
+pub mod board;
+pub mod daily;
+pub mod evil;
+pub mod merge;
+pub mod puzzle;
+pub mod record;
+pub mod save;
+pub mod scores;
+pub mod shape;
+pub mod spawn;
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_embed::RustEmbed;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use super::board::{Board, Direction};
+use super::save::SavedGame;
+use super::spawn::SpawnRules;
+
+const SOLVED_MAGIC: &str = "cosmic-ext-2048 solved 1";
+
+#[derive(RustEmbed)]
+#[folder = "res/puzzles/"]
+struct PuzzleFiles;
+
+/// A position to play from, with a tile to make in a limited number of moves.
+///
+/// Puzzles are a few header lines followed by a save file, so positions can be
+/// set up in the editor and saved:
+///
+/// ```text
+/// title Corner squeeze
+/// goal 512
+/// moves 10
+/// seed 7
+/// solution LLUR…
+/// cosmic-ext-2048 save 1
+/// 4x4
+/// score 0
+/// ...
+/// ```
+///
+/// The spawns are dealt from the seed, so the same moves always meet the same tiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    /// The name of the file the puzzle ships in, which also keeps track of it once solved.
+    pub id: String,
+    pub title: String,
+    pub goal: usize,
+    pub moves: usize,
+    pub seed: u64,
+    /// One way to make the goal in time, checked by the tests.
+    pub solution: Vec<Direction>,
+    pub width: usize,
+    pub height: usize,
+    pub spawn: SpawnRules,
+    pub board: Board,
+}
+impl Puzzle {
+    /// Every puzzle shipped with the game, in the order they are meant to be played.
+    pub fn all() -> Vec<Puzzle> {
+        let mut ids: Vec<String> = PuzzleFiles::iter().map(String::from).collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| {
+                let file = PuzzleFiles::get(&id).unwrap();
+                let input = String::from_utf8_lossy(&file.data).into_owned();
+                Puzzle::parse(&id, &input)
+                    .unwrap_or_else(|error| panic!("puzzle {id} is broken: {error}"))
+            })
+            .collect()
+    }
+
+    pub fn parse(id: &str, input: &str) -> Result<Puzzle, String> {
+        let start = input
+            .find("cosmic-ext-2048 save")
+            .ok_or("missing position")?;
+        let (header, position) = input.split_at(start);
+        let (mut title, mut goal, mut moves, mut seed) = (None, None, None, None);
+        let mut solution = vec![];
+        for line in header
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let number = || {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid {key} \"{value}\""))
+            };
+            match key {
+                "title" => title = Some(value.to_string()),
+                "goal" => goal = Some(number()?),
+                "moves" => moves = Some(number()?),
+                "seed" => {
+                    seed = Some(
+                        value
+                            .parse::<u64>()
+                            .map_err(|_| format!("invalid seed \"{value}\""))?,
+                    )
+                }
+                "solution" => {
+                    solution = value
+                        .chars()
+                        .map(|letter| {
+                            Direction::from_letter(letter)
+                                .ok_or_else(|| format!("invalid move \"{letter}\" in the solution"))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                _ => return Err(format!("unknown puzzle line \"{line}\"")),
+            }
+        }
+        let saved = SavedGame::parse(position)?;
+        let goal: usize = goal.ok_or("missing goal")?;
+        if !goal.is_power_of_two() || goal < 4 {
+            return Err(format!("the goal {goal} is not a tile"));
+        }
+        Ok(Puzzle {
+            id: id.to_string(),
+            title: title.ok_or("missing title")?,
+            goal,
+            moves: moves.ok_or("missing moves")?,
+            seed: seed.unwrap_or_default(),
+            solution,
+            width: saved.width,
+            height: saved.height,
+            spawn: saved.spawn,
+            board: saved.board,
+        })
+    }
+
+    /// Deals the spawns of the puzzle.
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
+
+    /// What to do, e.g. "Make a 512 in 10 moves".
+    pub fn task(&self) -> String {
+        let plural = if self.moves == 1 { "" } else { "s" };
+        format!("Make a {} in {} move{plural}", self.goal, self.moves)
+    }
+
+    pub fn is_solved_by(&self, board: &Board) -> bool {
+        board.max_tile() >= self.goal
+    }
+}
+
+/// The ids of the puzzles solved so far, kept next to the save file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolvedPuzzles(pub BTreeSet<String>);
+impl SolvedPuzzles {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("cosmic-ext-2048").join("puzzles.txt"))
+    }
+
+    /// Reads the solved puzzles, none until the first one is solved.
+    pub fn load() -> io::Result<SolvedPuzzles> {
+        let path = Self::default_path().ok_or(io::ErrorKind::NotFound)?;
+        match fs::read_to_string(path) {
+            Ok(input) => Self::parse(&input)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(SolvedPuzzles::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn store(&self) -> io::Result<()> {
+        let path = Self::default_path().ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())
+    }
+
+    pub fn parse(input: &str) -> Result<SolvedPuzzles, String> {
+        let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some(SOLVED_MAGIC) {
+            return Err(format!(
+                "not a list of solved puzzles, expected \"{SOLVED_MAGIC}\" on the first line"
+            ));
+        }
+        Ok(SolvedPuzzles(lines.map(String::from).collect()))
+    }
+}
+
+impl std::fmt::Display for SolvedPuzzles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{SOLVED_MAGIC}")?;
+        for id in &self.0 {
+            writeln!(f, "{id}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_puzzle_can_be_solved() {
+        let puzzles = Puzzle::all();
+        assert!(!puzzles.is_empty());
+        for puzzle in puzzles {
+            assert!(
+                !puzzle.is_solved_by(&puzzle.board),
+                "{} starts solved",
+                puzzle.id
+            );
+            assert!(
+                puzzle.solution.len() <= puzzle.moves,
+                "{} takes too long",
+                puzzle.id
+            );
+            let mut board = puzzle.board.clone();
+            let mut rng = puzzle.rng();
+            for &direction in &puzzle.solution {
+                board
+                    .move_with_rules(
+                        direction,
+                        puzzle.height,
+                        puzzle.width,
+                        &puzzle.spawn,
+                        &mut rng,
+                    )
+                    .unwrap_or_else(|| panic!("{} has a move that does nothing", puzzle.id));
+            }
+            assert!(puzzle.is_solved_by(&board), "{} is not solved", puzzle.id);
+        }
+    }
+
+    #[test]
+    fn solved_puzzles_round_trip() {
+        let solved = SolvedPuzzles(BTreeSet::from([
+            String::from("01-first-merge.txt"),
+            String::from("04-corner-squeeze.txt"),
+        ]));
+        assert_eq!(SolvedPuzzles::parse(&solved.to_string()), Ok(solved));
+    }
+
+    #[test]
+    fn rejects_puzzles_without_a_goal() {
+        let input =
+            "title Nothing to do\nmoves 3\ncosmic-ext-2048 save 1\n2x2\nscore 0\n2 .\n. 2\n";
+        assert_eq!(
+            Puzzle::parse("broken.txt", input),
+            Err(String::from("missing goal"))
+        );
+    }
+}
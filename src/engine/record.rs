@@ -0,0 +1,933 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::board::{Board, Direction, Spawn};
+use super::save::{tile_text, with_obstacles, MAX_SIDE};
+use super::spawn::SpawnRules;
+
+pub const VERSION: u32 = 1;
+
+/// Moves per line when writing the body of a record.
+const MOVES_PER_LINE: usize = 16;
+
+/// A whole game written down, in the spirit of PGN for chess.
+///
+/// ```text
+/// [Version "1"]
+/// [Size "4x4"]
+/// [Seed "1234"]
+/// [Mode "classic"]
+/// [Spawn "2:9 4:1"]
+/// [Score "16"]
+/// [Board "2,.,.,./.,4,.,./.,.,8,./.,.,.,2"]
+///
+/// L{5=2}U{3=2}RD{15=4}
+/// ```
+///
+/// Every move is the letter of its direction, optionally followed by the tiles
+/// that spawned after it as `{index=value}`, or `{index=value,index=value}` when
+/// the spawn rules deal several per move. Whitespace between moves is ignored.
+/// `Spawn` holds the `SpawnRules`. `Score` and `Board` describe the final
+/// position and are optional.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    pub mode: String,
+    pub spawn: SpawnRules,
+    /// Final score, if the game was finished when it was written down.
+    pub score: Option<usize>,
+    /// Final board, if the game was finished when it was written down.
+    pub board: Option<Board>,
+    pub moves: Vec<RecordedMove>,
+}
+
+/// The board and score after some number of moves of a recorded game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub board: Board,
+    pub score: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMove {
+    pub direction: Direction,
+    /// The tiles dealt after the move. Empty if they were not written down.
+    pub spawns: Vec<Spawn>,
+}
+
+/// The first point where a record disagrees with its own seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Number of the offending move, starting at 1. Disagreements about the
+    /// final position are reported on the last move.
+    pub move_number: usize,
+    pub message: String,
+}
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {}: {}", self.move_number, self.message)
+    }
+}
+
+/// Where and why a record could not be read. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+impl std::error::Error for ParseError {}
+
+fn error(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+    ParseError {
+        line,
+        column,
+        message: message.into(),
+    }
+}
+
+/// Spawns as written in the body, without the braces.
+fn spawns_tag(spawns: &[Spawn]) -> String {
+    spawns
+        .iter()
+        .map(|spawn| format!("{}={}", spawn.id, spawn.value))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Puts recorded `spawns` on `board` one after the other, failing on the
+/// first that lands on a tile, a blocker or outside the board.
+fn place_spawns(board: &mut Board, spawns: &[Spawn]) -> Result<(), String> {
+    for &spawn in spawns {
+        if board.place(spawn.id, spawn.value).is_none() {
+            let place = match board.0.get(spawn.id) {
+                Some(tile) if tile.hole => "outside the board",
+                Some(tile) if tile.blocked => "on a blocker",
+                Some(_) => "on a tile",
+                None => "outside the board",
+            };
+            return Err(format!(
+                "recorded spawn {} lands {place}",
+                spawns_tag(&[spawn])
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl GameRecord {
+    pub fn new(width: usize, height: usize, seed: u64) -> GameRecord {
+        GameRecord {
+            width,
+            height,
+            seed,
+            mode: String::from("classic"),
+            spawn: SpawnRules::classic(),
+            score: None,
+            board: None,
+            moves: vec![],
+        }
+    }
+
+    /// The random number generator the game was played with, at its first move.
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
+
+    /// The starting position, dealt from the seed.
+    pub fn start_board(&self, rng: &mut StdRng) -> Board {
+        self.spawn.start_board(self.height, self.width, rng)
+    }
+
+    /// Plays the game back, returning the start position followed by the
+    /// position after every move. Recorded spawns take precedence over the
+    /// seed. Stops early at a move that the board does not allow, that does
+    /// not change it or whose recorded spawns have nowhere to go.
+    pub fn positions(&self) -> Vec<Position> {
+        let mut rng = self.rng();
+        let mut position = Position {
+            board: self.start_board(&mut rng),
+            score: 0,
+        };
+        let mut positions = vec![position.clone()];
+        let directions = self.spawn.movement.directions();
+        for recorded in &self.moves {
+            if !directions.contains(&recorded.direction) {
+                break;
+            }
+            let Some(points) = position.board.slide(
+                recorded.direction,
+                self.height,
+                self.width,
+                self.spawn.movement,
+            ) else {
+                break;
+            };
+            // Always deal from the seed so that unannotated spawns stay in step.
+            let mut dealt = position.board.clone();
+            self.spawn
+                .spawn_after_move(&mut dealt, self.height, self.width, &mut rng);
+            if recorded.spawns.is_empty() {
+                position.board = dealt;
+            } else if place_spawns(&mut position.board, &recorded.spawns).is_err() {
+                break;
+            }
+            position.score += points;
+            positions.push(position.clone());
+        }
+        positions
+    }
+
+    /// Plays the game back strictly from the seed, checking every recorded
+    /// spawn and the final score and board. Returns the final position.
+    pub fn verify(&self) -> Result<Position, Divergence> {
+        let diverge = |move_number, message: String| Divergence {
+            move_number,
+            message,
+        };
+        let mut rng = self.rng();
+        let mut position = Position {
+            board: self.start_board(&mut rng),
+            score: 0,
+        };
+        let directions = self.spawn.movement.directions();
+        for (index, recorded) in self.moves.iter().enumerate() {
+            let move_number = index + 1;
+            if !directions.contains(&recorded.direction) {
+                return Err(diverge(
+                    move_number,
+                    format!(
+                        "{} is not a move on this board",
+                        recorded.direction.letter()
+                    ),
+                ));
+            }
+            let points = position
+                .board
+                .slide(
+                    recorded.direction,
+                    self.height,
+                    self.width,
+                    self.spawn.movement,
+                )
+                .ok_or_else(|| {
+                    diverge(
+                        move_number,
+                        format!("{} does not change the board", recorded.direction.letter()),
+                    )
+                })?;
+            place_spawns(&mut position.board.clone(), &recorded.spawns)
+                .map_err(|message| diverge(move_number, message))?;
+            let spawned =
+                self.spawn
+                    .spawn_after_move(&mut position.board, self.height, self.width, &mut rng);
+            if !recorded.spawns.is_empty() && spawned != recorded.spawns {
+                let dealt = if spawned.is_empty() {
+                    String::from("nothing")
+                } else {
+                    spawns_tag(&spawned)
+                };
+                return Err(diverge(
+                    move_number,
+                    format!(
+                        "recorded spawn {}, but the seed deals {dealt}",
+                        spawns_tag(&recorded.spawns)
+                    ),
+                ));
+            }
+            position.score += points;
+        }
+        let last = self.moves.len();
+        if let Some(score) = self.score.filter(|&score| score != position.score) {
+            return Err(diverge(
+                last,
+                format!(
+                    "recorded score {score}, but the moves score {}",
+                    position.score
+                ),
+            ));
+        }
+        if let Some(board) = self
+            .board
+            .as_ref()
+            .filter(|&board| *board != position.board)
+        {
+            return Err(diverge(
+                last,
+                format!(
+                    "recorded final board {}, but the moves end on {}",
+                    board_tag(board, self.width),
+                    board_tag(&position.board, self.width)
+                ),
+            ));
+        }
+        Ok(position)
+    }
+
+    /// Directory where the front-ends write finished records.
+    pub fn records_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("cosmic-ext-2048").join("records"))
+    }
+
+    /// Writes the record to `records_dir`, returning the file it ended up in.
+    pub fn store(&self) -> io::Result<PathBuf> {
+        self.store_as("game")
+    }
+
+    /// Like `store`, with the file named after `label` instead of "game", so
+    /// that games dealt from the same seed, like the two sides of a race, are
+    /// kept apart.
+    pub fn store_as(&self, label: &str) -> io::Result<PathBuf> {
+        let dir = Self::records_dir().ok_or(io::ErrorKind::NotFound)?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(self.file_name(label));
+        fs::write(&path, self.to_string())?;
+        Ok(path)
+    }
+
+    /// The name `store_as` gives the record, e.g. `game-42-310.txt` for a
+    /// game of 310 moves dealt from seed 42.
+    fn file_name(&self, label: &str) -> String {
+        format!("{label}-{}-{}.txt", self.seed, self.moves.len())
+    }
+
+    pub fn load(path: &std::path::Path) -> io::Result<GameRecord> {
+        Self::parse(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn parse(input: &str) -> Result<GameRecord, ParseError> {
+        let mut version = None;
+        let mut size = None;
+        let mut seed = None;
+        let mut mode = None;
+        let mut spawn = None;
+        let mut score = None;
+        let mut board = None;
+        let mut moves = vec![];
+        let mut in_body = false;
+        let mut last_line = 1;
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            last_line = line_number;
+            let trimmed = line.trim_start();
+            let indent = line.chars().count() - trimmed.chars().count();
+            if !in_body && trimmed.starts_with('[') {
+                let (key, value) = parse_tag(trimmed, line_number, indent + 1)?;
+                let value_column = indent + key.chars().count() + 4;
+                let slot = match key {
+                    "Version" => &mut version,
+                    "Size" => &mut size,
+                    "Seed" => &mut seed,
+                    "Mode" => &mut mode,
+                    "Spawn" => &mut spawn,
+                    "Score" => &mut score,
+                    "Board" => &mut board,
+                    _ => {
+                        return Err(error(
+                            line_number,
+                            indent + 2,
+                            format!("unknown tag \"{key}\""),
+                        ))
+                    }
+                };
+                if slot.is_some() {
+                    return Err(error(
+                        line_number,
+                        indent + 2,
+                        format!("duplicate tag \"{key}\""),
+                    ));
+                }
+                *slot = Some((value.to_string(), line_number, value_column));
+                continue;
+            }
+            if trimmed.is_empty() {
+                continue;
+            }
+            in_body = true;
+            parse_moves(line, line_number, &mut moves)?;
+        }
+
+        let missing = |key: &str| error(last_line, 1, format!("missing tag \"{key}\""));
+        let (version_value, line, column) = version.ok_or_else(|| missing("Version"))?;
+        if version_value.parse::<u32>().ok() != Some(VERSION) {
+            return Err(error(
+                line,
+                column,
+                format!("unsupported version \"{version_value}\", expected \"{VERSION}\""),
+            ));
+        }
+        let (size_value, line, column) = size.ok_or_else(|| missing("Size"))?;
+        let (width, height) = size_value
+            .split_once('x')
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .ok_or_else(|| {
+                error(
+                    line,
+                    column,
+                    format!("invalid size \"{size_value}\", expected WIDTHxHEIGHT"),
+                )
+            })?;
+        if ![width, height]
+            .iter()
+            .all(|side: &usize| (2..=MAX_SIDE).contains(side))
+        {
+            return Err(error(
+                line,
+                column,
+                format!("invalid size \"{size_value}\", the sides must be 2 to {MAX_SIDE}"),
+            ));
+        }
+        let (seed_value, line, column) = seed.ok_or_else(|| missing("Seed"))?;
+        let seed = seed_value
+            .parse()
+            .map_err(|_| error(line, column, format!("invalid seed \"{seed_value}\"")))?;
+        let (mode, _, _) = mode.ok_or_else(|| missing("Mode"))?;
+        let (spawn_value, line, column) = spawn.ok_or_else(|| missing("Spawn"))?;
+        let spawn = SpawnRules::parse(&spawn_value).map_err(|message| {
+            error(
+                line,
+                column,
+                format!("invalid spawn rules \"{spawn_value}\": {message}"),
+            )
+        })?;
+        let score =
+            match score {
+                Some((score_value, line, column)) => Some(score_value.parse().map_err(|_| {
+                    error(line, column, format!("invalid score \"{score_value}\""))
+                })?),
+                None => None,
+            };
+
+        let board = match board {
+            Some((board_value, line, column)) => {
+                Some(parse_board(&board_value, width, height).ok_or_else(|| {
+                    error(line, column, format!("invalid board \"{board_value}\""))
+                })?)
+            }
+            None => None,
+        };
+
+        let cells = width * height;
+        for (recorded, line, column) in &moves {
+            if let Some(spawn) = recorded.spawns.iter().find(|spawn| spawn.id >= cells) {
+                return Err(error(
+                    *line,
+                    *column,
+                    format!(
+                        "spawn on tile {}, but the board only has {cells} tiles",
+                        spawn.id
+                    ),
+                ));
+            }
+        }
+
+        Ok(GameRecord {
+            width,
+            height,
+            seed,
+            mode,
+            spawn,
+            score,
+            board,
+            moves: moves.into_iter().map(|(recorded, _, _)| recorded).collect(),
+        })
+    }
+}
+
+/// Reads a `Board` tag: tiles separated by commas, rows by slashes.
+fn parse_board(value: &str, width: usize, height: usize) -> Option<Board> {
+    let rows: Vec<&str> = value.split('/').collect();
+    if rows.len() != height {
+        return None;
+    }
+    let mut contents = vec![];
+    let mut blockers = vec![];
+    let mut holes = vec![];
+    for row in rows {
+        let cells: Vec<&str> = row.split(',').map(str::trim).collect();
+        if cells.len() != width {
+            return None;
+        }
+        for cell in cells {
+            contents.push(match cell {
+                "." => None,
+                "#" => {
+                    blockers.push(contents.len());
+                    None
+                }
+                "-" => {
+                    holes.push(contents.len());
+                    None
+                }
+                value => Some(value.parse().ok().filter(|&value: &usize| value > 0)?),
+            });
+        }
+    }
+    Some(with_obstacles(contents, &blockers, &holes))
+}
+
+fn board_tag(board: &Board, width: usize) -> String {
+    board
+        .0
+        .chunks(width)
+        .map(|row| row.iter().map(tile_text).collect::<Vec<String>>().join(","))
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Splits `[Key "Value"]` into its key and value.
+fn parse_tag(tag: &str, line: usize, column: usize) -> Result<(&str, &str), ParseError> {
+    let inner = tag.trim_end().strip_suffix(']').ok_or_else(|| {
+        error(
+            line,
+            column + tag.trim_end().chars().count(),
+            "expected ']'",
+        )
+    })?;
+    let inner = &inner[1..];
+    let (key, value) = inner
+        .split_once(' ')
+        .ok_or_else(|| error(line, column + 1, "expected a key and a quoted value"))?;
+    let value_column = column + key.chars().count() + 2;
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(|| error(line, value_column, "expected a quoted value"))?;
+    Ok((key, value))
+}
+
+/// Reads the moves on one line of the body, remembering where each one started.
+fn parse_moves(
+    line: &str,
+    line_number: usize,
+    moves: &mut Vec<(RecordedMove, usize, usize)>,
+) -> Result<(), ParseError> {
+    let mut chars = line.chars().enumerate().peekable();
+    while let Some((index, c)) = chars.next() {
+        let column = index + 1;
+        if c.is_whitespace() {
+            continue;
+        }
+        let direction = Direction::from_letter(c).ok_or_else(|| {
+            let letters: Vec<String> = Direction::HEX
+                .iter()
+                .map(|direction| direction.letter().to_string())
+                .collect();
+            error(
+                line_number,
+                column,
+                format!("unexpected '{c}', expected one of {}", letters.join(", ")),
+            )
+        })?;
+        let mut spawns = vec![];
+        if chars.peek().is_some_and(|&(_, c)| c == '{') {
+            let (open, _) = chars.next().unwrap();
+            let mut annotation = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '}')) => break,
+                    Some((_, c)) => annotation.push(c),
+                    None => {
+                        return Err(error(line_number, open + 1, "unclosed '{'"));
+                    }
+                }
+            }
+            for spawn in annotation.split(',') {
+                spawns.push(
+                    spawn
+                        .split_once('=')
+                        .and_then(|(id, value)| {
+                            Some(Spawn {
+                                id: id.trim().parse().ok()?,
+                                value: value.trim().parse().ok()?,
+                            })
+                        })
+                        .ok_or_else(|| {
+                            error(
+                                line_number,
+                                open + 2,
+                                format!("invalid spawn \"{annotation}\", expected {{index=value}}"),
+                            )
+                        })?,
+                );
+            }
+        }
+        moves.push((RecordedMove { direction, spawns }, line_number, column));
+    }
+    Ok(())
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "[Version \"{VERSION}\"]")?;
+        writeln!(f, "[Size \"{}x{}\"]", self.width, self.height)?;
+        writeln!(f, "[Seed \"{}\"]", self.seed)?;
+        writeln!(f, "[Mode \"{}\"]", self.mode)?;
+        writeln!(f, "[Spawn \"{}\"]", self.spawn)?;
+        if let Some(score) = self.score {
+            writeln!(f, "[Score \"{score}\"]")?;
+        }
+        if let Some(board) = &self.board {
+            writeln!(f, "[Board \"{}\"]", board_tag(board, self.width))?;
+        }
+        writeln!(f)?;
+        for line in self.moves.chunks(MOVES_PER_LINE) {
+            for recorded in line {
+                write!(f, "{}", recorded.direction.letter())?;
+                if !recorded.spawns.is_empty() {
+                    write!(f, "{{{}}}", spawns_tag(&recorded.spawns))?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> GameRecord {
+        let mut record = GameRecord::new(4, 3, 1234);
+        record.score = Some(20);
+        record.moves = (0..40)
+            .map(|index| RecordedMove {
+                direction: Direction::ALL[index % 4],
+                spawns: (index % 3 != 0)
+                    .then_some(Spawn {
+                        id: index % 12,
+                        value: if index % 5 == 0 { 4 } else { 2 },
+                    })
+                    .into_iter()
+                    .collect(),
+            })
+            .collect();
+        record
+    }
+
+    #[test]
+    fn round_trip() {
+        let record = sample();
+        assert_eq!(GameRecord::parse(&record.to_string()), Ok(record));
+    }
+
+    #[test]
+    fn round_trip_without_moves_or_score() {
+        let record = GameRecord::new(5, 5, u64::MAX);
+        assert_eq!(GameRecord::parse(&record.to_string()), Ok(record));
+    }
+
+    #[test]
+    fn serializes_compact_moves() {
+        let mut record = GameRecord::new(4, 4, 7);
+        record.moves = vec![
+            RecordedMove {
+                direction: Direction::Left,
+                spawns: vec![Spawn { id: 5, value: 2 }],
+            },
+            RecordedMove {
+                direction: Direction::Up,
+                spawns: vec![],
+            },
+        ];
+        assert_eq!(
+            record.to_string(),
+            "[Version \"1\"]\n[Size \"4x4\"]\n[Seed \"7\"]\n[Mode \"classic\"]\n\
+             [Spawn \"2:9 4:1\"]\n\nL{5=2}U\n"
+        );
+    }
+
+    #[test]
+    fn ignores_whitespace_between_moves() {
+        let record = GameRecord::parse(
+            "[Version \"1\"]\n[Size \"2x2\"]\n[Seed \"1\"]\n[Mode \"classic\"]\n\
+             [Spawn \"2:9 4:1\"]\n\n L R\n\n U{0=2}  D \n",
+        )
+        .unwrap();
+        let letters: String = record
+            .moves
+            .iter()
+            .map(|recorded| recorded.direction.letter())
+            .collect();
+        assert_eq!(letters, "LRUD");
+        assert_eq!(record.moves[2].spawns, vec![Spawn { id: 0, value: 2 }]);
+    }
+
+    fn error_at(input: &str) -> (usize, usize) {
+        let error = GameRecord::parse(input).unwrap_err();
+        (error.line, error.column)
+    }
+
+    const HEADER: &str =
+        "[Version \"1\"]\n[Size \"4x4\"]\n[Seed \"1\"]\n[Mode \"classic\"]\n[Spawn \"2:9 4:1\"]\n";
+
+    #[test]
+    fn rejects_sizes_out_of_range() {
+        let header = |size: &str| {
+            format!(
+                "[Version \"1\"]\n[Size \"{size}\"]\n[Seed \"1\"]\n[Mode \"classic\"]\n\
+                 [Spawn \"2:9 4:1\"]\n"
+            )
+        };
+        assert_eq!(error_at(&header("33x4")), (2, 8));
+        assert_eq!(error_at(&header("4294967296x4294967296")), (2, 8));
+        assert_eq!(error_at(&header("1x4")), (2, 8));
+        assert!(GameRecord::parse(&header("32x2")).is_ok());
+    }
+
+    #[test]
+    fn reports_bad_move_position() {
+        assert_eq!(error_at(&format!("{HEADER}\nLUR\nDDX")), (8, 3));
+    }
+
+    #[test]
+    fn reports_bad_spawn_position() {
+        assert_eq!(error_at(&format!("{HEADER}\nLU{{3-2}}")), (7, 4));
+        assert_eq!(error_at(&format!("{HEADER}\nLU{{3=2")), (7, 3));
+        assert_eq!(error_at(&format!("{HEADER}\nLU{{16=2}}")), (7, 2));
+    }
+
+    #[test]
+    fn reports_bad_header_position() {
+        assert_eq!(
+            error_at(
+                "[Version \"2\"]\n[Size \"4x4\"]\n[Seed \"1\"]\n[Mode \"classic\"]\n[Spawn \"\"]\n"
+            ),
+            (1, 11)
+        );
+        assert_eq!(
+            error_at("[Version \"1\"]\n[Size \"4by4\"]\n[Seed \"1\"]\n[Mode \"classic\"]\n[Spawn \"\"]\n"),
+            (2, 8)
+        );
+        assert_eq!(error_at("[Version \"1\"]\n  [Colour \"red\"]\n"), (2, 4));
+        assert_eq!(error_at("[Version \"1\"\n"), (1, 13));
+    }
+
+    /// A game of `moves` played from `seed`, written down with all its spawns.
+    fn played(seed: u64, moves: usize) -> GameRecord {
+        played_with(SpawnRules::classic(), seed, moves)
+    }
+
+    fn played_with(rules: SpawnRules, seed: u64, moves: usize) -> GameRecord {
+        let mut record = GameRecord::new(4, 4, seed);
+        record.spawn = rules;
+        let mut rng = record.rng();
+        let mut board = record.start_board(&mut rng);
+        let mut score = 0;
+        while record.moves.len() < moves {
+            // Starts from a different direction every move, so all of them get played.
+            let directions = record.spawn.movement.directions();
+            let turn = directions.iter().cycle().skip(record.moves.len());
+            let Some((direction, moved)) = turn.take(directions.len()).find_map(|&direction| {
+                Some((
+                    direction,
+                    board.move_with_rules(direction, 4, 4, &record.spawn, &mut rng)?,
+                ))
+            }) else {
+                break;
+            };
+            score += moved.points;
+            record.moves.push(RecordedMove {
+                direction,
+                spawns: moved.spawned,
+            });
+        }
+        record.score = Some(score);
+        record.board = Some(board);
+        record
+    }
+
+    #[test]
+    fn verifies_honest_record() {
+        let record = played(42, 200);
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(
+            parsed.verify().map(|position| position.score),
+            Ok(record.score.unwrap())
+        );
+    }
+
+    #[test]
+    fn verifies_record_with_custom_spawn_rules() {
+        let rules = SpawnRules::parse("2:1 4:1 8:1 start=5 each=2 region=edges").unwrap();
+        let record = played_with(rules, 7, 100);
+        assert!(record
+            .moves
+            .iter()
+            .any(|recorded| recorded.spawns.len() == 2));
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        assert_eq!(parsed, record);
+        assert!(parsed.verify().is_ok());
+        assert_eq!(
+            parsed.positions().last().map(|position| &position.board),
+            record.board.as_ref()
+        );
+    }
+
+    #[test]
+    fn verifies_hex_record() {
+        let rules = SpawnRules::parse("2:9 4:1 grid=hex wrap").unwrap();
+        let record = played_with(rules, 11, 100);
+        assert!(record
+            .moves
+            .iter()
+            .any(|recorded| recorded.direction == Direction::UpRight));
+        assert!(record.to_string().contains("grid=hex wrap"));
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(
+            parsed.verify().map(|position| position.score),
+            Ok(record.score.unwrap())
+        );
+    }
+
+    #[test]
+    fn verifies_threes_record() {
+        let rules = SpawnRules::presets()
+            .into_iter()
+            .find_map(|(name, rules)| (name == "Threes").then_some(rules))
+            .unwrap();
+        let record = played_with(rules, 5, 100);
+        assert!(record.to_string().contains("merge=threes step preview"));
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(
+            parsed.verify().map(|position| position.score),
+            Ok(record.score.unwrap())
+        );
+    }
+
+    #[test]
+    fn preview_shows_the_next_spawn() {
+        let mut record = GameRecord::new(4, 4, 9);
+        record.spawn = SpawnRules::parse("1:1 2:1 3:1 start=9 merge=threes step preview").unwrap();
+        let mut rng = record.rng();
+        let mut board = record.start_board(&mut rng);
+        let mut previewed = 0;
+        for &direction in Direction::ALL.iter().cycle().take(200) {
+            let next = record.spawn.next_value(&rng);
+            let Some(moved) = board.move_with_rules(direction, 4, 4, &record.spawn, &mut rng)
+            else {
+                continue;
+            };
+            if let Some(spawn) = moved.spawned.first() {
+                assert_eq!(next, Some(spawn.value));
+                previewed += 1;
+            }
+        }
+        assert!(previewed > 10);
+        record.spawn.preview = false;
+        assert_eq!(record.spawn.next_value(&rng), None);
+    }
+
+    #[test]
+    fn rejects_diagonal_moves_on_square_boards() {
+        let mut record = played(42, 30);
+        record.moves[10].direction = Direction::UpRight;
+        let divergence = record.verify().unwrap_err();
+        assert_eq!(divergence.move_number, 11);
+        assert_eq!(record.positions().len(), 11);
+    }
+
+    #[test]
+    fn reports_first_divergent_spawn() {
+        let mut record = played(42, 200);
+        let spawn = &mut record.moves[57].spawns[0];
+        spawn.value = if spawn.value == 2 { 4 } else { 2 };
+        assert_eq!(record.verify().unwrap_err().move_number, 58);
+    }
+
+    #[test]
+    fn reports_tampered_score() {
+        let mut record = played(42, 200);
+        record.score = record.score.map(|score| score + 4);
+        assert_eq!(record.verify().unwrap_err().move_number, record.moves.len());
+    }
+
+    #[test]
+    fn reports_missing_tags() {
+        let error = GameRecord::parse("[Version \"1\"]\n\nLURD\n").unwrap_err();
+        assert_eq!(error.message, "missing tag \"Size\"");
+    }
+
+    #[test]
+    fn rejects_spawns_on_cells_without_room() {
+        let rules = SpawnRules::parse("2:9 4:1 blockers=2 shape=donut").unwrap();
+        let record = played_with(rules, 7, 20);
+        let before = &record.positions()[4].board;
+        let after = &record.positions()[5].board;
+        let dealt = record.moves[4].spawns[0];
+        let blocker = before.0.iter().find(|tile| tile.blocked && !tile.hole);
+        let hole = before.0.iter().find(|tile| tile.hole);
+        let taken = after
+            .0
+            .iter()
+            .find(|tile| tile.tilecontent.is_some() && tile.id != dealt.id);
+        for (tile, place) in [
+            (blocker, "on a blocker"),
+            (hole, "outside the board"),
+            (taken, "on a tile"),
+        ] {
+            let mut tampered = record.clone();
+            tampered.moves[4].spawns[0].id = tile.unwrap().id;
+            let divergence = tampered.verify().unwrap_err();
+            assert_eq!(divergence.move_number, 5);
+            assert_eq!(
+                divergence.message,
+                format!(
+                    "recorded spawn {}={} lands {place}",
+                    tile.unwrap().id,
+                    dealt.value
+                )
+            );
+            assert_eq!(tampered.positions().len(), 5);
+        }
+    }
+
+    #[test]
+    fn racers_on_one_seed_get_the_same_deal() {
+        let racer = |directions: &[Direction]| {
+            let mut record = GameRecord::new(4, 4, 31);
+            let mut rng = record.rng();
+            let mut board = record.start_board(&mut rng);
+            for &direction in directions {
+                let moved = board
+                    .move_with_rules(direction, 4, 4, &record.spawn, &mut rng)
+                    .unwrap();
+                record.moves.push(RecordedMove {
+                    direction,
+                    spawns: moved.spawned,
+                });
+            }
+            record
+        };
+        let first = racer(&[Direction::Left, Direction::Up]);
+        let second = racer(&[Direction::Left, Direction::Down]);
+        assert_eq!(first.positions()[..2], second.positions()[..2]);
+        assert_eq!(first.moves[0], second.moves[0]);
+        assert!(first.verify().is_ok() && second.verify().is_ok());
+        // Both records of a race are kept, named after their player.
+        assert_eq!(first.file_name("race-player1"), "race-player1-31-2.txt");
+        assert_ne!(
+            first.file_name("race-player1"),
+            second.file_name("race-player2")
+        );
+    }
+}
@@ -0,0 +1,442 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use super::board::{pair_to_index, Board, Tile};
+use super::merge::Merge;
+use super::spawn::SpawnRules;
+
+const MAGIC: &str = "cosmic-ext-2048 save 1";
+/// Prefix of the one-line form used on the clipboard.
+const COMPACT_MAGIC: &str = "2048";
+/// Separates the tiles of the one-line form from the spawn rules, if any.
+const COMPACT_RULES: char = ';';
+/// Largest board side accepted from save files and the clipboard.
+pub const MAX_SIDE: usize = 32;
+/// How a blocker is written in place of a tile.
+const BLOCKER: &str = "#";
+/// How a cell outside the shape of the board is written.
+const HOLE: &str = "-";
+
+/// A game in progress, as written to disk by every front-end.
+///
+/// The format is plain text so that it can be read and fixed by hand:
+///
+/// ```text
+/// cosmic-ext-2048 save 1
+/// 4x4
+/// score 1024
+/// spawn 2:9 4:1
+/// 2 . . 4
+/// . . . .
+/// . 8 . .
+/// . . . 2
+/// ```
+///
+/// The `spawn` line holds the `SpawnRules` and may be left out for the classic ones.
+/// Blockers are written as `#` and cells outside the shape of the board as `-`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedGame {
+    pub width: usize,
+    pub height: usize,
+    pub score: usize,
+    pub spawn: SpawnRules,
+    pub board: Board,
+}
+impl SavedGame {
+    /// Where the front-ends keep their save file.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("cosmic-ext-2048").join("save.txt"))
+    }
+
+    pub fn load() -> io::Result<SavedGame> {
+        let path = Self::default_path().ok_or(io::ErrorKind::NotFound)?;
+        Self::parse(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn store(&self) -> io::Result<()> {
+        let path = Self::default_path().ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())
+    }
+
+    pub fn parse(input: &str) -> Result<SavedGame, String> {
+        let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some(MAGIC) {
+            return Err(format!(
+                "not a save file, expected \"{MAGIC}\" on the first line"
+            ));
+        }
+        let (width, height) = parse_size(lines.next().ok_or("missing board size")?)?;
+        let score = lines
+            .next()
+            .and_then(|line| line.strip_prefix("score "))
+            .and_then(|score| score.parse().ok())
+            .ok_or("missing score")?;
+        let mut lines = lines.peekable();
+        let spawn = match lines.next_if(|line| line.starts_with("spawn ")) {
+            Some(line) => SpawnRules::parse(&line["spawn ".len()..])
+                .map_err(|error| format!("invalid spawn rules: {error}"))?,
+            None => SpawnRules::classic(),
+        };
+
+        let mut contents = vec![];
+        let mut blockers = vec![];
+        let mut holes = vec![];
+        for h in 0..height {
+            let row = lines
+                .next()
+                .ok_or_else(|| format!("missing row {}", h + 1))?;
+            let cells: Vec<&str> = row.split_whitespace().collect();
+            if cells.len() != width {
+                return Err(format!(
+                    "row {} has {} tiles instead of {width}",
+                    h + 1,
+                    cells.len()
+                ));
+            }
+            for cell in cells {
+                match cell {
+                    BLOCKER => blockers.push(contents.len()),
+                    HOLE => holes.push(contents.len()),
+                    _ => {}
+                }
+                contents.push(parse_tile(cell, h, spawn.movement.merge)?);
+            }
+        }
+        if lines.next().is_some() {
+            return Err(String::from("unexpected data after the last row"));
+        }
+        Ok(SavedGame {
+            width,
+            height,
+            score,
+            spawn,
+            board: with_obstacles(contents, &blockers, &holes),
+        })
+    }
+
+    /// One-line form for sharing, e.g. `2048:4x4:1024:2,.,.,4/.,.,.,./.,8,.,./.,.,.,2`.
+    /// Rules other than the classic ones follow the tiles after a `;`, as in
+    /// `2048:2x2:0:1,./.,2;1:9 2:1 merge=fibonacci`.
+    pub fn compact(&self) -> String {
+        let rows: Vec<String> = self
+            .board
+            .0
+            .chunks(self.width)
+            .map(|row| row.iter().map(tile_text).collect::<Vec<String>>().join(","))
+            .collect();
+        let rules = if self.spawn == SpawnRules::classic() {
+            String::new()
+        } else {
+            format!("{COMPACT_RULES}{}", self.spawn)
+        };
+        format!(
+            "{COMPACT_MAGIC}:{}x{}:{}:{}{rules}",
+            self.width,
+            self.height,
+            self.score,
+            rows.join("/")
+        )
+    }
+
+    /// The board as coloured squares, for pasting into a chat.
+    pub fn emoji_grid(&self) -> String {
+        format!(
+            "2048 {}x{}, score {}, best tile {}\n{}",
+            self.width,
+            self.height,
+            self.score,
+            self.board.max_tile(),
+            self.emoji_rows()
+        )
+    }
+
+    /// Just the squares of `emoji_grid`, one line per row.
+    pub fn emoji_rows(&self) -> String {
+        let mut out = String::new();
+        for row in self.board.0.chunks(self.width) {
+            row.iter()
+                .for_each(|tile| out.push_str(emoji(tile, self.spawn.movement.merge)));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Reads a position pasted from the clipboard, either in the compact form or
+    /// as a whole save file.
+    pub fn from_clipboard(input: &str) -> Result<SavedGame, String> {
+        let input = input.trim();
+        if input.starts_with(MAGIC) {
+            Self::parse(input)
+        } else {
+            Self::parse_compact(input)
+        }
+    }
+
+    pub fn parse_compact(input: &str) -> Result<SavedGame, String> {
+        let (position, spawn) = match input.trim().split_once(COMPACT_RULES) {
+            Some((position, rules)) => (
+                position,
+                SpawnRules::parse(rules)
+                    .map_err(|error| format!("invalid spawn rules: {error}"))?,
+            ),
+            None => (input.trim(), SpawnRules::classic()),
+        };
+        let mut fields = position.split(':');
+        if fields.next() != Some(COMPACT_MAGIC) {
+            return Err(format!(
+                "not a 2048 position, expected it to start with \"{COMPACT_MAGIC}:\""
+            ));
+        }
+        let (width, height) = parse_size(fields.next().ok_or("missing board size")?)?;
+        let score = fields
+            .next()
+            .and_then(|score| score.parse().ok())
+            .ok_or("missing score")?;
+        let tiles = fields.next().ok_or("missing tiles")?;
+        if fields.next().is_some() {
+            return Err(String::from("unexpected data after the tiles"));
+        }
+
+        let rows: Vec<&str> = tiles.split('/').collect();
+        if rows.len() != height {
+            return Err(format!("{} rows instead of {height}", rows.len()));
+        }
+        let mut contents = vec![];
+        let mut blockers = vec![];
+        let mut holes = vec![];
+        for (h, row) in rows.into_iter().enumerate() {
+            let cells: Vec<&str> = row.split(',').map(str::trim).collect();
+            if cells.len() != width {
+                return Err(format!(
+                    "row {} has {} tiles instead of {width}",
+                    h + 1,
+                    cells.len()
+                ));
+            }
+            for cell in cells {
+                match cell {
+                    BLOCKER => blockers.push(contents.len()),
+                    HOLE => holes.push(contents.len()),
+                    _ => {}
+                }
+                contents.push(parse_tile(cell, h, spawn.movement.merge)?);
+            }
+        }
+        Ok(SavedGame {
+            width,
+            height,
+            score,
+            spawn,
+            board: with_obstacles(contents, &blockers, &holes),
+        })
+    }
+}
+
+/// A board size such as `4x4`, with both sides from 2 to `MAX_SIDE`.
+fn parse_size(size: &str) -> Result<(usize, usize), String> {
+    let (width, height) = size
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .ok_or_else(|| format!("invalid board size: {size}"))?;
+    if width > MAX_SIDE || height > MAX_SIDE {
+        return Err(format!(
+            "the board is {width}x{height}, at most {MAX_SIDE}x{MAX_SIDE} is supported"
+        ));
+    }
+    if width < 2 || height < 2 {
+        return Err(String::from("the board must be at least 2x2"));
+    }
+    Ok((width, height))
+}
+
+/// A tile is either `.`, a blocker, a hole or a value `merge` can make, such
+/// as a power of two from 2 up.
+fn parse_tile(cell: &str, row: usize, merge: Merge) -> Result<Option<usize>, String> {
+    match cell {
+        "." | BLOCKER | HOLE => Ok(None),
+        value => match value.parse::<usize>() {
+            Ok(value) if merge.rule().is_tile(value) => Ok(Some(value)),
+            _ => Err(format!("invalid tile on row {}: {value}", row + 1)),
+        },
+    }
+}
+
+/// Builds a board from row-major tile contents, then blocks the cells
+/// `blockers` and cuts the cells `holes` out.
+pub(crate) fn with_obstacles(
+    contents: Vec<Option<usize>>,
+    blockers: &[usize],
+    holes: &[usize],
+) -> Board {
+    let mut board = Board::from_contents(contents);
+    blockers.iter().for_each(|&id| board.block(id));
+    holes.iter().for_each(|&id| board.cut(id));
+    board
+}
+
+/// A tile as written in save files and game records.
+pub(crate) fn tile_text(tile: &Tile) -> String {
+    match tile.tilecontent {
+        _ if tile.hole => String::from(HOLE),
+        _ if tile.blocked => String::from(BLOCKER),
+        Some(content) => content.to_string(),
+        None => String::from("."),
+    }
+}
+
+fn emoji(tile: &Tile, merge: Merge) -> &'static str {
+    // From the smallest tile upwards, everything past the last one shares the star.
+    const SQUARES: [&str; 11] = [
+        "⬜", "🟨", "🟧", "🟥", "🟪", "🟦", "🟩", "🟫", "🔷", "🔶", "⭐",
+    ];
+    match tile.tilecontent {
+        _ if tile.hole => "\u{3000}",
+        _ if tile.blocked => "🧱",
+        None => "⬛",
+        Some(content) => {
+            let rank = merge.rule().rank(content);
+            SQUARES[rank.saturating_sub(1).min(SQUARES.len() - 1)]
+        }
+    }
+}
+
+impl std::fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{MAGIC}")?;
+        writeln!(f, "{}x{}", self.width, self.height)?;
+        writeln!(f, "score {}", self.score)?;
+        writeln!(f, "spawn {}", self.spawn)?;
+        for h in 0..self.height {
+            let row: Vec<String> = (0..self.width)
+                .map(|w| tile_text(&self.board.0[pair_to_index(h, w, self.width)]))
+                .collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn compact_form_keeps_the_rules() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for (name, spawn) in SpawnRules::presets() {
+            let saved = SavedGame {
+                width: 5,
+                height: 4,
+                score: 12,
+                board: spawn.start_board(4, 5, &mut rng),
+                spawn,
+            };
+            let pasted = SavedGame::from_clipboard(&saved.compact());
+            assert_eq!(pasted, Ok(saved), "{name}");
+        }
+    }
+
+    #[test]
+    fn compact_form_defaults_to_classic() {
+        let saved = SavedGame::from_clipboard("2048:2x2:8:2,./.,4").unwrap();
+        assert_eq!(saved.spawn, SpawnRules::classic());
+        assert_eq!(saved.compact(), "2048:2x2:8:2,./.,4");
+        assert!(SavedGame::from_clipboard("2048:2x2:8:1,./.,3").is_err());
+        assert!(SavedGame::from_clipboard("2048:2x2:8:1,./.,3;1:1 2:1 3:1 merge=threes").is_ok());
+        assert!(SavedGame::from_clipboard("2048:2x2:8:2,./.,4;2:1 merge=tripling").is_err());
+    }
+
+    #[test]
+    fn save_file_round_trip() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for (name, spawn) in SpawnRules::presets() {
+            let saved = SavedGame {
+                width: 3,
+                height: 5,
+                score: 2048,
+                board: spawn.start_board(5, 3, &mut rng),
+                spawn,
+            };
+            assert_eq!(SavedGame::parse(&saved.to_string()), Ok(saved), "{name}");
+        }
+    }
+
+    #[test]
+    fn rejects_broken_save_files() {
+        let save = |body: &str| SavedGame::parse(&format!("{MAGIC}\n{body}"));
+        assert!(save("2x2\nscore 4\n2 .\n. 2\n").is_ok());
+        assert!(SavedGame::parse("cosmic-ext-2048 save 0\n2x2\nscore 4\n2 .\n. 2\n").is_err());
+        assert_eq!(
+            save("2by2\nscore 4\n2 .\n. 2\n"),
+            Err(String::from("invalid board size: 2by2"))
+        );
+        assert_eq!(save("2x2\n2 .\n. 2\n"), Err(String::from("missing score")));
+        assert_eq!(
+            save("2x2\nscore 4\n2 .\n"),
+            Err(String::from("missing row 2"))
+        );
+        assert_eq!(
+            save("2x2\nscore 4\n2 . .\n. 2\n"),
+            Err(String::from("row 1 has 3 tiles instead of 2"))
+        );
+        assert_eq!(
+            save("2x2\nscore 4\n2 .\n. 3\n"),
+            Err(String::from("invalid tile on row 2: 3"))
+        );
+        assert_eq!(
+            save("2x2\nscore 4\n2 .\n. 2\n. .\n"),
+            Err(String::from("unexpected data after the last row"))
+        );
+        assert!(save("2x2\nscore 4\nspawn 2:0\n2 .\n. 2\n").is_err());
+    }
+
+    #[test]
+    fn rejects_sizes_out_of_range() {
+        let save = |size: &str| SavedGame::parse(&format!("{MAGIC}\n{size}\nscore 0\n. .\n. .\n"));
+        assert!(save("2x2").is_ok());
+        assert_eq!(
+            save("1x2"),
+            Err(String::from("the board must be at least 2x2"))
+        );
+        assert_eq!(
+            save("2x33"),
+            Err(String::from(
+                "the board is 2x33, at most 32x32 is supported"
+            ))
+        );
+        assert_eq!(
+            save("2x99999999999999999999"),
+            Err(String::from("invalid board size: 2x99999999999999999999"))
+        );
+        assert!(SavedGame::from_clipboard("2048:1x2:0:.").is_err());
+        assert!(SavedGame::from_clipboard("2048:33x1:0:.").is_err());
+    }
+
+    #[test]
+    fn edited_positions_round_trip() {
+        let spawn = SpawnRules::parse("2:9 4:1 grid=hex shape=cross").unwrap();
+        let mut board = spawn.empty_board(5, 5);
+        let cells: Vec<usize> = (0..25).filter(|&id| !board.0[id].hole).collect();
+        board.block(cells[0]);
+        board.cut(cells[1]);
+        board.place(cells[2], 64).unwrap();
+        board.place(cells[3], 2).unwrap();
+        let saved = SavedGame {
+            width: 5,
+            height: 5,
+            score: 100,
+            spawn,
+            board,
+        };
+        assert_eq!(SavedGame::parse(&saved.to_string()).as_ref(), Ok(&saved));
+        assert_eq!(SavedGame::from_clipboard(&saved.compact()), Ok(saved));
+    }
+}
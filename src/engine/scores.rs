@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const MAGIC: &str = "cosmic-ext-2048 scores 2";
+/// The first version, from before hints were counted.
+const MAGIC_1: &str = "cosmic-ext-2048 scores 1";
+/// Entries kept per mode.
+pub const KEPT: usize = 10;
+
+/// One finished game in the high-score table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub score: usize,
+    pub max_tile: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Hints asked for during the game.
+    pub hints: usize,
+    /// The kind of game, e.g. "Classic" or "Evil: Cruel". Every mode has its own table.
+    pub mode: String,
+}
+
+/// The best games of every mode, shared by all front-ends.
+///
+/// Stored as one line per game with its score, best tile, size and hints used,
+/// the mode last since it may contain spaces:
+///
+/// ```text
+/// cosmic-ext-2048 scores 2
+/// 20480 2048 4x4 0 Classic
+/// 3012 256 4x4 5 Evil: Cruel
+/// ```
+///
+/// Files of the first version, without the hints, still load with no hints used.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HighScores(pub Vec<HighScore>);
+impl HighScores {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("cosmic-ext-2048").join("scores.txt"))
+    }
+
+    /// Reads the table, which is empty until the first game is over.
+    pub fn load() -> io::Result<HighScores> {
+        let path = Self::default_path().ok_or(io::ErrorKind::NotFound)?;
+        match fs::read_to_string(path) {
+            Ok(input) => Self::parse(&input)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(HighScores::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn store(&self) -> io::Result<()> {
+        let path = Self::default_path().ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())
+    }
+
+    pub fn parse(input: &str) -> Result<HighScores, String> {
+        let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+        let with_hints = match lines.next() {
+            Some(MAGIC) => true,
+            Some(MAGIC_1) => false,
+            _ => {
+                return Err(format!(
+                    "not a score file, expected \"{MAGIC}\" on the first line"
+                ))
+            }
+        };
+        let mut scores = HighScores::default();
+        for line in lines {
+            let (score, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let (max_tile, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+            let (size, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+            let (hints, mode) = if with_hints {
+                rest.split_once(' ').unwrap_or((rest, ""))
+            } else {
+                ("0", rest)
+            };
+            let entry = size
+                .split_once('x')
+                .and_then(|(width, height)| {
+                    Some(HighScore {
+                        score: score.parse().ok()?,
+                        max_tile: max_tile.parse().ok()?,
+                        width: width.parse().ok()?,
+                        height: height.parse().ok()?,
+                        hints: hints.parse().ok()?,
+                        mode: mode.to_string(),
+                    })
+                })
+                .filter(|entry| !entry.mode.is_empty())
+                .ok_or_else(|| format!("invalid score line: {line}"))?;
+            scores.0.push(entry);
+        }
+        Ok(scores)
+    }
+
+    /// The table of one mode, best first.
+    pub fn table(&self, mode: &str) -> Vec<&HighScore> {
+        let mut table: Vec<&HighScore> = self.0.iter().filter(|entry| entry.mode == mode).collect();
+        table.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        table
+    }
+
+    /// Every mode with at least one entry, by name.
+    pub fn modes(&self) -> Vec<&str> {
+        let mut modes: Vec<&str> = self.0.iter().map(|entry| entry.mode.as_str()).collect();
+        modes.sort_unstable();
+        modes.dedup();
+        modes
+    }
+
+    /// Adds a finished game, returning its rank (1 for the best) if it made the table.
+    pub fn add(&mut self, entry: HighScore) -> Option<usize> {
+        let mode = entry.mode.clone();
+        let score = entry.score;
+        self.0.push(entry);
+        // Ties go to the older game, so the new one ranks after every equal score.
+        let table = self.table(&mode);
+        let rank = table.iter().filter(|kept| kept.score >= score).count();
+        let kept: Vec<HighScore> = table.into_iter().take(KEPT).cloned().collect();
+        self.0.retain(|entry| entry.mode != mode);
+        self.0.extend(kept);
+        (rank <= KEPT).then_some(rank)
+    }
+}
+
+impl std::fmt::Display for HighScores {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{MAGIC}")?;
+        for entry in &self.0 {
+            writeln!(
+                f,
+                "{} {} {}x{} {} {}",
+                entry.score, entry.max_tile, entry.width, entry.height, entry.hints, entry.mode
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut scores = HighScores::default();
+        scores.add(HighScore {
+            score: 3012,
+            max_tile: 256,
+            width: 4,
+            height: 4,
+            hints: 5,
+            mode: String::from("Evil: Cruel"),
+        });
+        assert_eq!(HighScores::parse(&scores.to_string()), Ok(scores));
+    }
+
+    #[test]
+    fn reads_scores_from_before_hints() {
+        let scores =
+            HighScores::parse("cosmic-ext-2048 scores 1\n20480 2048 4x4 Evil: Cruel\n").unwrap();
+        assert_eq!(scores.0[0].hints, 0);
+        assert_eq!(scores.0[0].mode, "Evil: Cruel");
+        assert!(HighScores::parse("cosmic-ext-2048 scores 2\n20480 2048 4x4 Classic\n").is_err());
+    }
+
+    #[test]
+    fn errors_show_the_whole_line() {
+        assert_eq!(
+            HighScores::parse("cosmic-ext-2048 scores 2\n20480 2048 4by4 0 Classic\n"),
+            Err(String::from(
+                "invalid score line: 20480 2048 4by4 0 Classic"
+            ))
+        );
+    }
+
+    #[test]
+    fn time_attack_scores_are_kept_apart() {
+        let entry = |score, mode: &str| HighScore {
+            score,
+            max_tile: 256,
+            width: 4,
+            height: 4,
+            hints: 0,
+            mode: String::from(mode),
+        };
+        let mut scores = HighScores::default();
+        assert_eq!(scores.add(entry(5000, "Classic")), Some(1));
+        assert_eq!(
+            scores.add(entry(1200, "Classic, 3 min time attack")),
+            Some(1)
+        );
+        assert_eq!(
+            scores.add(entry(900, "Classic, 1 min time attack")),
+            Some(1)
+        );
+        assert_eq!(
+            scores.add(entry(1000, "Classic, 3 min time attack")),
+            Some(2)
+        );
+        assert_eq!(scores.table("Classic").len(), 1);
+        assert_eq!(scores.table("Classic, 3 min time attack").len(), 2);
+        assert_eq!(
+            scores.modes(),
+            [
+                "Classic",
+                "Classic, 1 min time attack",
+                "Classic, 3 min time attack"
+            ]
+        );
+        assert_eq!(HighScores::parse(&scores.to_string()), Ok(scores));
+    }
+}
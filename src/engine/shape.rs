@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use super::board::Board;
+
+/// The outline of the board. Cells outside it are holes: they never hold a
+/// tile and stop the tiles like blockers do, but are not drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Rectangle,
+    /// The middle third of the rows and of the columns.
+    Cross,
+    Diamond,
+    /// The rectangle without its top right quarter.
+    L,
+    /// The rectangle without its middle third, on boards of at least 3x3.
+    Donut,
+}
+impl Shape {
+    pub const ALL: [Shape; 5] = [
+        Shape::Rectangle,
+        Shape::Cross,
+        Shape::Diamond,
+        Shape::L,
+        Shape::Donut,
+    ];
+
+    /// The name used in spawn rules, e.g. `shape=cross`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Shape::Rectangle => "rectangle",
+            Shape::Cross => "cross",
+            Shape::Diamond => "diamond",
+            Shape::L => "l",
+            Shape::Donut => "donut",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Shape> {
+        Shape::ALL.into_iter().find(|shape| shape.name() == name)
+    }
+
+    /// The name shown in the menu.
+    pub fn label(self) -> &'static str {
+        match self {
+            Shape::Rectangle => "Rectangle",
+            Shape::Cross => "Cross",
+            Shape::Diamond => "Diamond",
+            Shape::L => "L",
+            Shape::Donut => "Donut",
+        }
+    }
+
+    /// Whether the cell `id` is part of a board of this shape.
+    pub fn contains(self, id: usize, height: usize, width: usize) -> bool {
+        let (h, w) = (id / width, id % width);
+        // The rows and columns of the middle third, at least one of each.
+        let middle = |at: usize, side: usize| (side / 3..side - side / 3).contains(&at);
+        match self {
+            Shape::Rectangle => true,
+            Shape::Cross => middle(h, height) || middle(w, width),
+            Shape::Diamond => {
+                // Distances from the centre, doubled so that they stay whole.
+                let from_middle_row = (2 * h).abs_diff(height - 1);
+                let from_middle_column = (2 * w).abs_diff(width - 1);
+                from_middle_row * width + from_middle_column * height <= width * height
+            }
+            Shape::L => h >= height / 2 || w < width - width / 2,
+            Shape::Donut => height < 3 || width < 3 || !(middle(h, height) && middle(w, width)),
+        }
+    }
+
+    /// Cuts the cells outside the shape out of `board`.
+    pub fn cut(self, board: &mut Board, height: usize, width: usize) {
+        (0..height * width)
+            .filter(|&id| !self.contains(id, height, width))
+            .for_each(|id| board.cut(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::engine::board::Direction;
+    use crate::engine::save::SavedGame;
+    use crate::engine::spawn::SpawnRules;
+
+    /// The shape on a board, `#` for the cells that are part of it.
+    fn outline(shape: Shape, height: usize, width: usize) -> String {
+        (0..height)
+            .map(|h| {
+                (0..width)
+                    .map(|w| {
+                        if shape.contains(h * width + w, height, width) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    #[test]
+    fn outlines() {
+        assert_eq!(outline(Shape::Cross, 5, 5), ".###./#####/#####/#####/.###.");
+        assert_eq!(
+            outline(Shape::Diamond, 5, 5),
+            "..#../.###./#####/.###./..#.."
+        );
+        assert_eq!(outline(Shape::L, 4, 4), "##../##../####/####");
+        assert_eq!(outline(Shape::Donut, 4, 4), "####/#..#/#..#/####");
+        // Too small to have a hole in the middle.
+        assert_eq!(outline(Shape::Donut, 2, 4), "####/####");
+    }
+
+    #[test]
+    fn holes_stay_empty() {
+        let rules = SpawnRules {
+            shape: Shape::Donut,
+            ..SpawnRules::classic()
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut board = rules.start_board(5, 5, &mut rng);
+        for direction in Direction::ALL.iter().cycle().take(200) {
+            board.move_with_rules(*direction, 5, 5, &rules, &mut rng);
+        }
+        for (id, tile) in board.0.iter().enumerate() {
+            assert_eq!(tile.hole, !Shape::Donut.contains(id, 5, 5));
+            assert!(!tile.hole || tile.tilecontent.is_none());
+        }
+        let saved = SavedGame {
+            width: 5,
+            height: 5,
+            score: 0,
+            spawn: rules,
+            board,
+        };
+        assert_eq!(SavedGame::parse(&saved.to_string()), Ok(saved));
+    }
+}
@@ -0,0 +1,535 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::Rng;
+use std::fmt;
+
+use super::board::{Board, Grid, Movement, Spawn};
+use super::evil::{self, Malice};
+use super::merge::Merge;
+use super::shape::Shape;
+
+/// Largest weight a spawn value may have.
+pub const MAX_WEIGHT: usize = 1000;
+
+/// The part of the board new tiles may appear in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Anywhere,
+    /// The outermost rows and columns.
+    Edges,
+    Corners,
+    /// Everything but the edges, or the whole board if it has no inside.
+    Centre,
+}
+impl Region {
+    pub const ALL: [Region; 4] = [
+        Region::Anywhere,
+        Region::Edges,
+        Region::Corners,
+        Region::Centre,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Region::Anywhere => "anywhere",
+            Region::Edges => "edges",
+            Region::Corners => "corners",
+            Region::Centre => "centre",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Region> {
+        Region::ALL.into_iter().find(|region| region.name() == name)
+    }
+
+    pub fn contains(self, id: usize, height: usize, width: usize) -> bool {
+        let (h, w) = (id / width, id % width);
+        let on_row_edge = h == 0 || h == height - 1;
+        let on_column_edge = w == 0 || w == width - 1;
+        match self {
+            Region::Anywhere => true,
+            Region::Edges => on_row_edge || on_column_edge,
+            Region::Corners => on_row_edge && on_column_edge,
+            Region::Centre => (height <= 2 || width <= 2) || !(on_row_edge || on_column_edge),
+        }
+    }
+}
+
+/// How tiles are put on the board: at the start and after every move.
+///
+/// In game records and save files the rules are written as the weighted values,
+/// followed by whatever differs from the classic game:
+///
+/// ```text
+/// 2:9 4:1
+/// 2:1 4:1 8:1 start=4 each=2 region=corners
+/// 2:9 4:1 evil=cruel
+/// 2:9 4:1 blockers=2
+/// 2:9 4:1 shape=cross
+/// 2:9 4:1 wrap
+/// 2:9 4:1 grid=hex
+/// 1:9 2:1 merge=fibonacci
+/// 1:1 2:1 3:1 start=9 region=edges merge=threes step preview
+/// ```
+///
+/// Besides the spawns they carry the rest of what makes a variant, such as the
+/// shape of the board and how the tiles move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnRules {
+    /// Values that can spawn, each with its relative weight.
+    pub weights: Vec<(usize, usize)>,
+    /// Tiles on the board when the game starts.
+    pub start_tiles: usize,
+    /// Tiles spawned after every move, as long as there is room.
+    pub per_move: usize,
+    pub region: Region,
+    /// Deals the tiles on purpose instead of at random, after the start.
+    pub opponent: Option<Malice>,
+    /// Cells turned into blockers when the game starts.
+    pub blockers: usize,
+    /// The outline of the board, the cells outside it are holes.
+    pub shape: Shape,
+    pub movement: Movement,
+    /// The next value is drawn ahead of time so the front-ends can show it.
+    pub preview: bool,
+}
+
+impl Default for SpawnRules {
+    fn default() -> SpawnRules {
+        SpawnRules::classic()
+    }
+}
+
+impl SpawnRules {
+    /// Nine 2s to one 4, two tiles to start with and one more after every move.
+    pub fn classic() -> SpawnRules {
+        let mut weights: Vec<(usize, usize)> = vec![];
+        Board::TWO_OR_FOUR.iter().for_each(|&value| {
+            match weights.iter_mut().find(|(known, _)| *known == value) {
+                Some((_, weight)) => *weight += 1,
+                None => weights.push((value, 1)),
+            }
+        });
+        SpawnRules {
+            weights,
+            start_tiles: 2,
+            per_move: 1,
+            region: Region::Anywhere,
+            opponent: None,
+            blockers: 0,
+            shape: Shape::Rectangle,
+            movement: Movement::default(),
+            preview: false,
+        }
+    }
+
+    /// Rule sets offered by the front-ends, by name.
+    pub fn presets() -> Vec<(&'static str, SpawnRules)> {
+        let classic = SpawnRules::classic();
+        vec![
+            ("Classic", classic.clone()),
+            (
+                "Even 2s and 4s",
+                SpawnRules {
+                    weights: vec![(2, 1), (4, 1)],
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Big start",
+                SpawnRules {
+                    start_tiles: 6,
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Two per move",
+                SpawnRules {
+                    per_move: 2,
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Edges only",
+                SpawnRules {
+                    region: Region::Edges,
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Corners only",
+                SpawnRules {
+                    region: Region::Corners,
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Obstacles",
+                SpawnRules {
+                    blockers: 2,
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Wrap around",
+                SpawnRules {
+                    movement: Movement {
+                        wrap: true,
+                        ..classic.movement
+                    },
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Hexagons",
+                SpawnRules {
+                    movement: Movement {
+                        grid: Grid::Hex,
+                        ..classic.movement
+                    },
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Fibonacci",
+                SpawnRules {
+                    weights: vec![(1, 9), (2, 1)],
+                    movement: Movement {
+                        merge: Merge::Fibonacci,
+                        ..classic.movement
+                    },
+                    ..classic.clone()
+                },
+            ),
+            (
+                "Threes",
+                SpawnRules {
+                    weights: vec![(1, 1), (2, 1), (3, 1)],
+                    start_tiles: 9,
+                    region: Region::Edges,
+                    movement: Movement {
+                        step: true,
+                        merge: Merge::Threes,
+                        ..classic.movement
+                    },
+                    preview: true,
+                    ..classic
+                },
+            ),
+        ]
+    }
+
+    /// Names the kind of game for the high scores: "Classic", "Evil: Cruel" when
+    /// only the opponent differs, and the rules themselves otherwise.
+    pub fn mode_name(&self) -> String {
+        let tame = SpawnRules {
+            opponent: None,
+            ..self.clone()
+        };
+        match (self.opponent, tame == SpawnRules::classic()) {
+            (None, true) => String::from("Classic"),
+            (Some(malice), true) => format!("Evil: {}", malice.label()),
+            (_, false) => format!("Custom: {self}"),
+        }
+    }
+
+    /// Probability of each value, for the solver.
+    pub fn odds(&self) -> Vec<(usize, f64)> {
+        let total = self.weights.iter().map(|(_, weight)| weight).sum::<usize>() as f64;
+        self.weights
+            .iter()
+            .map(|&(value, weight)| (value, weight as f64 / total))
+            .collect()
+    }
+
+    /// Draws a value. The values are counted off like `Board::TWO_OR_FOUR` and
+    /// drawn like `choose` draws from a slice, so the classic rules deal exactly
+    /// what they always did for a given seed.
+    pub fn pick_value(&self, rng: &mut impl Rng) -> usize {
+        let total: usize = self.weights.iter().map(|(_, weight)| weight).sum();
+        let mut at = rng.gen_range(0..total as u32) as usize;
+        for &(value, weight) in &self.weights {
+            if at < weight {
+                return value;
+            }
+            at -= weight;
+        }
+        unreachable!("the draw is below the total weight")
+    }
+
+    /// The value `spawn_one` will put on the board next, when the rules show it.
+    /// Drawing it from a copy of `rng` leaves the game's own generator alone.
+    pub fn next_value<R: Rng + Clone>(&self, rng: &R) -> Option<usize> {
+        (self.preview && self.opponent.is_none()).then(|| self.pick_value(&mut rng.clone()))
+    }
+
+    /// Cuts the cells outside the grid and the shape out of `board`.
+    pub fn cut_outline(&self, board: &mut Board, height: usize, width: usize) {
+        self.movement.grid.cut(board, height, width);
+        self.shape.cut(board, height, width);
+    }
+
+    /// An empty board of these rules, with only the outline cut out.
+    pub fn empty_board(&self, height: usize, width: usize) -> Board {
+        let mut board = Board::from_contents(vec![None; width * height]);
+        self.cut_outline(&mut board, height, width);
+        board
+    }
+
+    /// The starting position. The grid and the shape are cut out first, then
+    /// the blockers are placed anywhere on what is left.
+    pub fn start_board(&self, height: usize, width: usize, rng: &mut impl Rng) -> Board {
+        let mut board = self.empty_board(height, width);
+        // Only shuffled when needed, so games without blockers deal as they always did.
+        if self.blockers > 0 {
+            let mut ids: Vec<usize> = (0..(width * height))
+                .filter(|&id| board.0[id].is_empty())
+                .collect();
+            ids.shuffle(rng);
+            ids.iter()
+                .take(self.blockers)
+                .for_each(|&id| board.block(id));
+        }
+        let mut ids: Vec<usize> = (0..(width * height))
+            .filter(|&id| board.0[id].is_empty() && self.region.contains(id, height, width))
+            .collect();
+        ids.shuffle(rng);
+        ids.iter().take(self.start_tiles).for_each(|&id| {
+            board.0[id].tilecontent = Some(self.pick_value(rng));
+        });
+        board
+    }
+
+    /// Puts one tile on a random empty cell of the region, if there is one. With
+    /// a preview the value is drawn before the cell, as `next_value` expects.
+    pub fn spawn_one(
+        &self,
+        board: &mut Board,
+        height: usize,
+        width: usize,
+        rng: &mut impl Rng,
+    ) -> Option<Spawn> {
+        if let Some(malice) = self.opponent {
+            let spawn = evil::worst_spawn(board, height, width, self, malice, rng)?;
+            return board.place(spawn.id, spawn.value);
+        }
+        let value = self.preview.then(|| self.pick_value(rng));
+        let id = board
+            .0
+            .iter()
+            .filter(|tile| tile.is_empty())
+            .map(|tile| tile.id)
+            .filter(|&id| self.region.contains(id, height, width))
+            .choose(rng)?;
+        board.place(id, value.unwrap_or_else(|| self.pick_value(rng)))
+    }
+
+    /// The tiles that appear after a move.
+    pub fn spawn_after_move(
+        &self,
+        board: &mut Board,
+        height: usize,
+        width: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<Spawn> {
+        (0..self.per_move)
+            .map_while(|_| self.spawn_one(board, height, width, rng))
+            .collect()
+    }
+
+    pub fn parse(input: &str) -> Result<SpawnRules, String> {
+        let mut rules = SpawnRules {
+            weights: vec![],
+            ..SpawnRules::classic()
+        };
+        for word in input.split_whitespace() {
+            let flag = match word {
+                "wrap" => Some(&mut rules.movement.wrap),
+                "step" => Some(&mut rules.movement.step),
+                "preview" => Some(&mut rules.preview),
+                _ => None,
+            };
+            if let Some(flag) = flag {
+                *flag = true;
+                continue;
+            }
+            if let Some((key, value)) = word.split_once('=') {
+                let count = || {
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid {key} \"{value}\""))
+                };
+                match key {
+                    "start" => rules.start_tiles = count()?,
+                    "each" => rules.per_move = count()?,
+                    "blockers" => rules.blockers = count()?,
+                    "region" => {
+                        rules.region = Region::from_name(value)
+                            .ok_or_else(|| format!("unknown region \"{value}\""))?
+                    }
+                    "grid" => {
+                        rules.movement.grid = Grid::from_name(value)
+                            .ok_or_else(|| format!("unknown grid \"{value}\""))?
+                    }
+                    "merge" => {
+                        rules.movement.merge = Merge::from_name(value)
+                            .ok_or_else(|| format!("unknown merge rule \"{value}\""))?
+                    }
+                    "shape" => {
+                        rules.shape = Shape::from_name(value)
+                            .ok_or_else(|| format!("unknown shape \"{value}\""))?
+                    }
+                    "evil" => {
+                        rules.opponent = Some(
+                            Malice::from_name(value)
+                                .ok_or_else(|| format!("unknown opponent \"{value}\""))?,
+                        )
+                    }
+                    _ => return Err(format!("unknown spawn option \"{key}\"")),
+                }
+                continue;
+            }
+            let (value, weight) = word
+                .split_once(':')
+                .and_then(|(value, weight)| {
+                    Some((value.parse::<usize>().ok()?, weight.parse::<usize>().ok()?))
+                })
+                .filter(|&(value, weight)| value > 0 && weight > 0)
+                .ok_or_else(|| format!("invalid spawn weight \"{word}\", expected value:weight"))?;
+            if weight > MAX_WEIGHT {
+                return Err(format!("the weight of {value} is above {MAX_WEIGHT}"));
+            }
+            if rules.weights.iter().any(|&(known, _)| known == value) {
+                return Err(format!("{value} is weighted twice"));
+            }
+            rules.weights.push((value, weight));
+        }
+        if rules.weights.is_empty() {
+            return Err(String::from("no spawn values"));
+        }
+        let merge = rules.movement.merge;
+        if let Some((value, _)) = rules
+            .weights
+            .iter()
+            .find(|&&(value, _)| !merge.rule().is_tile(value))
+        {
+            return Err(format!(
+                "{value} is not a tile when merging by {}",
+                merge.name()
+            ));
+        }
+        if rules.movement.wrap && rules.movement.step {
+            return Err(String::from(
+                "tiles that step one cell at a time cannot wrap",
+            ));
+        }
+        if rules.per_move == 0 {
+            return Err(String::from("at least one tile must spawn per move"));
+        }
+        Ok(rules)
+    }
+}
+
+impl fmt::Display for SpawnRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let weights: Vec<String> = self
+            .weights
+            .iter()
+            .map(|(value, weight)| format!("{value}:{weight}"))
+            .collect();
+        write!(f, "{}", weights.join(" "))?;
+        let classic = SpawnRules::classic();
+        if self.start_tiles != classic.start_tiles {
+            write!(f, " start={}", self.start_tiles)?;
+        }
+        if self.per_move != classic.per_move {
+            write!(f, " each={}", self.per_move)?;
+        }
+        if self.region != classic.region {
+            write!(f, " region={}", self.region.name())?;
+        }
+        if let Some(malice) = self.opponent {
+            write!(f, " evil={}", malice.name())?;
+        }
+        if self.blockers != classic.blockers {
+            write!(f, " blockers={}", self.blockers)?;
+        }
+        if self.shape != classic.shape {
+            write!(f, " shape={}", self.shape.name())?;
+        }
+        if self.movement.grid != classic.movement.grid {
+            write!(f, " grid={}", self.movement.grid.name())?;
+        }
+        if self.movement.merge != classic.movement.merge {
+            write!(f, " merge={}", self.movement.merge.name())?;
+        }
+        if self.movement.wrap {
+            write!(f, " wrap")?;
+        }
+        if self.movement.step {
+            write!(f, " step")?;
+        }
+        if self.preview {
+            write!(f, " preview")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn picks_like_choosing_from_the_values() {
+        let rules = SpawnRules::parse("2:9 4:1 8:3").unwrap();
+        let values = [[2; 9].as_slice(), &[4], &[8; 3]].concat();
+        let (mut rng, mut chosen) = (StdRng::seed_from_u64(1), StdRng::seed_from_u64(1));
+        for _ in 0..1000 {
+            assert_eq!(
+                rules.pick_value(&mut rng),
+                *values.choose(&mut chosen).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn weights_are_limited() {
+        assert!(SpawnRules::parse(&format!("2:{MAX_WEIGHT}")).is_ok());
+        assert!(SpawnRules::parse("2:100000000000").is_err());
+    }
+
+    #[test]
+    fn empty_boards_have_the_outline_of_a_game() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for (name, rules) in SpawnRules::presets() {
+            let empty = rules.empty_board(5, 6);
+            let dealt = rules.start_board(5, 6, &mut rng);
+            assert!(
+                empty.0.iter().all(|tile| tile.tilecontent.is_none()),
+                "{name}"
+            );
+            assert!(
+                empty.0.iter().all(|tile| tile.blocked == tile.hole),
+                "{name}"
+            );
+            let holes =
+                |board: &Board| -> Vec<bool> { board.0.iter().map(|tile| tile.hole).collect() };
+            assert_eq!(holes(&empty), holes(&dealt), "{name}");
+        }
+    }
+
+    #[test]
+    fn modes_are_named_after_the_rules() {
+        assert_eq!(SpawnRules::classic().mode_name(), "Classic");
+        let evil = SpawnRules {
+            opponent: Some(Malice::Cruel),
+            ..SpawnRules::classic()
+        };
+        assert_eq!(evil.mode_name(), "Evil: Cruel");
+        let custom = SpawnRules::parse("2:1 4:1").unwrap();
+        assert_eq!(custom.mode_name(), "Custom: 2:1 4:1");
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Animated GIFs of recorded games.
+use std::io::Write;
+
+use gif::{Encoder, Frame, Repeat};
+
+use super::raster;
+use crate::engine::record::GameRecord;
+
+/// How long the final position stays up, in frame delays.
+const FINAL_FRAME_HOLD: u16 = 4;
+
+pub struct AnimationOptions {
+    /// Time each move stays on screen, in milliseconds.
+    pub frame_delay: u32,
+    /// Whether every frame shows the score above the board.
+    pub caption: bool,
+}
+
+/// Encodes one frame per position of `record` into `out`.
+pub fn write_gif(
+    record: &GameRecord,
+    options: &AnimationOptions,
+    out: impl Write,
+) -> Result<(), gif::EncodingError> {
+    let positions = record.positions();
+    let score = options.caption.then_some(0);
+    let movement = record.spawn.movement;
+    let first = raster::render(
+        &positions[0].board,
+        record.height,
+        record.width,
+        movement,
+        score,
+    );
+    let (width, height) = (first.width() as u16, first.height() as u16);
+    let mut encoder = Encoder::new(out, width, height, &[])?;
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    // GIF delays are in hundredths of a second.
+    let delay = (options.frame_delay / 10).clamp(1, u32::from(u16::MAX)) as u16;
+    for (index, position) in positions.iter().enumerate() {
+        let score = options.caption.then_some(position.score);
+        let mut pixmap = raster::render(
+            &position.board,
+            record.height,
+            record.width,
+            movement,
+            score,
+        );
+        let mut frame = Frame::from_rgba_speed(width, height, pixmap.data_mut(), 10);
+        frame.delay = if index + 1 == positions.len() {
+            delay.saturating_mul(FINAL_FRAME_HOLD)
+        } else {
+            delay
+        };
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::board::Direction;
+    use crate::engine::record::RecordedMove;
+
+    fn frames(record: &GameRecord, caption: bool) -> (u16, u16, Vec<u16>) {
+        let options = AnimationOptions {
+            frame_delay: 200,
+            caption,
+        };
+        let mut gif = vec![];
+        write_gif(record, &options, &mut gif).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(gif.as_slice()).unwrap();
+        let mut delays = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        (decoder.width(), decoder.height(), delays)
+    }
+
+    #[test]
+    fn one_frame_per_position() {
+        let mut record = GameRecord::new(3, 2, 4);
+        record.moves = [Direction::Left, Direction::Right, Direction::Left]
+            .into_iter()
+            .map(|direction| RecordedMove {
+                direction,
+                spawns: vec![],
+            })
+            .collect();
+        let positions = record.positions().len();
+        assert!(positions > 1);
+        let (width, height, delays) = frames(&record, true);
+        // Three 100 pixel columns and two rows, with the caption on top.
+        assert_eq!((width, height), (348, 288));
+        assert_eq!(delays.len(), positions);
+        assert!(delays[..positions - 1].iter().all(|&delay| delay == 20));
+        assert_eq!(delays[positions - 1], 20 * FINAL_FRAME_HOLD);
+        let (_, uncaptioned, _) = frames(&record, false);
+        assert_eq!(uncaptioned, 240);
+    }
+}
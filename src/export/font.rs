@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! A tiny stroke font for drawing numbers and captions without a font library.
+//!
+//! Glyphs are polylines on a grid `WIDTH` units wide and `HEIGHT` units tall,
+//! with y growing downwards.
+
+pub const WIDTH: f32 = 4.0;
+pub const HEIGHT: f32 = 6.0;
+/// Distance between the left edges of two neighbouring glyphs.
+pub const ADVANCE: f32 = 6.0;
+
+type Glyph = &'static [&'static [(u8, u8)]];
+
+const ZERO: Glyph = &[&[(0, 0), (4, 0), (4, 6), (0, 6), (0, 0)]];
+const ONE: Glyph = &[&[(1, 1), (2, 0), (2, 6)], &[(1, 6), (3, 6)]];
+const TWO: Glyph = &[&[(0, 0), (4, 0), (4, 3), (0, 3), (0, 6), (4, 6)]];
+const THREE: Glyph = &[&[(0, 0), (4, 0), (4, 6), (0, 6)], &[(0, 3), (4, 3)]];
+const FOUR: Glyph = &[&[(0, 0), (0, 3), (4, 3)], &[(4, 0), (4, 6)]];
+const FIVE: Glyph = &[&[(4, 0), (0, 0), (0, 3), (4, 3), (4, 6), (0, 6)]];
+const SIX: Glyph = &[&[(4, 0), (0, 0), (0, 6), (4, 6), (4, 3), (0, 3)]];
+const SEVEN: Glyph = &[&[(0, 0), (4, 0), (4, 6)]];
+const EIGHT: Glyph = &[&[(0, 0), (4, 0), (4, 6), (0, 6), (0, 0)], &[(0, 3), (4, 3)]];
+const NINE: Glyph = &[&[(4, 3), (0, 3), (0, 0), (4, 0), (4, 6), (0, 6)]];
+const C: Glyph = &[&[(4, 0), (0, 0), (0, 6), (4, 6)]];
+const E: Glyph = &[&[(4, 0), (0, 0), (0, 6), (4, 6)], &[(0, 3), (3, 3)]];
+const R: Glyph = &[&[(0, 6), (0, 0), (4, 0), (4, 3), (0, 3)], &[(2, 3), (4, 6)]];
+const X: Glyph = &[&[(0, 0), (4, 6)], &[(4, 0), (0, 6)]];
+
+/// Strokes of `c`; characters without a glyph are drawn as blanks.
+pub fn glyph(c: char) -> Glyph {
+    match c.to_ascii_uppercase() {
+        '0' | 'O' => ZERO,
+        '1' => ONE,
+        '2' => TWO,
+        '3' => THREE,
+        '4' => FOUR,
+        '5' | 'S' => FIVE,
+        '6' => SIX,
+        '7' => SEVEN,
+        '8' => EIGHT,
+        '9' => NINE,
+        'C' => C,
+        'E' => E,
+        'R' => R,
+        'X' => X,
+        _ => &[],
+    }
+}
+
+/// Width of `text` drawn with glyphs `size` pixels tall.
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let scale = size / HEIGHT;
+    let count = text.chars().count() as f32;
+    (count * ADVANCE - (ADVANCE - WIDTH)).max(0.0) * scale
+}
+
+/// The strokes of `text` in pixels, with its top left corner at `(x, y)`.
+pub fn strokes(text: &str, x: f32, y: f32, size: f32) -> Vec<Vec<(f32, f32)>> {
+    let scale = size / HEIGHT;
+    text.chars()
+        .enumerate()
+        .flat_map(|(index, c)| {
+            let left = x + index as f32 * ADVANCE * scale;
+            glyph(c).iter().map(move |line| {
+                line.iter()
+                    .map(|&(gx, gy)| (left + f32::from(gx) * scale, y + f32::from(gy) * scale))
+                    .collect()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Drawing boards outside of the COSMIC app: pictures and terminal colours.
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::engine::board::{Board, Grid, Movement};
+
+pub mod animation;
+pub mod font;
+pub mod raster;
+pub mod svg;
+
+/// An sRGB colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+const WHITE: Rgb = Rgb(255, 255, 255);
+/// Text on the two smallest tiles, which are too light for white.
+pub const DARK_TEXT: Rgb = Rgb(119, 110, 101);
+pub const BOARD: Rgb = Rgb(187, 173, 160);
+pub const EMPTY: Rgb = Rgb(205, 193, 180);
+pub const BLOCKER: Rgb = Rgb(94, 84, 76);
+/// Tiles from rank 1 up, see `MergeRule::rank`: the tiles from 2 to 2048 of the
+/// classic game. The COSMIC app draws its tiles with these colours too.
+pub const TILE_PALETTE: [Rgb; 11] = [
+    Rgb(238, 228, 218),
+    Rgb(237, 224, 200),
+    Rgb(242, 177, 121),
+    Rgb(245, 149, 99),
+    Rgb(246, 124, 96),
+    Rgb(246, 94, 59),
+    Rgb(237, 207, 115),
+    Rgb(237, 204, 98),
+    Rgb(237, 200, 80),
+    Rgb(237, 197, 63),
+    Rgb(237, 194, 45),
+];
+
+/// Background and text colour of a tile of `rank`. Ranks past the palette
+/// are black.
+pub fn tile_colors(rank: usize) -> (Rgb, Rgb) {
+    match rank
+        .checked_sub(1)
+        .and_then(|index| TILE_PALETTE.get(index))
+    {
+        Some(&background) if rank <= 2 => (background, DARK_TEXT),
+        Some(&background) => (background, WHITE),
+        None => (Rgb(0, 0, 0), WHITE),
+    }
+}
+
+/// Sizes of a drawn board, in pixels.
+pub struct Layout {
+    pub tile: f32,
+    pub gap: f32,
+    pub padding: f32,
+    /// Height of the caption above the board, zero without one.
+    pub caption: f32,
+    /// Hex boards are drawn like in the app, every column half a cell below
+    /// the one on its left.
+    pub grid: Grid,
+}
+impl Layout {
+    pub const DEFAULT: Layout = Layout {
+        tile: 100.0,
+        gap: 8.0,
+        padding: 16.0,
+        caption: 48.0,
+        grid: Grid::Square,
+    };
+
+    /// How far column `w` hangs below the top of the board.
+    fn drop(&self, w: usize) -> f32 {
+        match self.grid {
+            Grid::Square => 0.0,
+            Grid::Hex => w as f32 * (self.tile + self.gap) / 2.0,
+        }
+    }
+
+    pub fn image_size(&self, height: usize, width: usize) -> (f32, f32) {
+        (
+            self.padding * 2.0 + width as f32 * (self.tile + self.gap) - self.gap,
+            self.padding * 2.0
+                + self.caption
+                + height as f32 * (self.tile + self.gap)
+                + self.drop(width - 1)
+                - self.gap,
+        )
+    }
+
+    /// Top left corner of the tile on row `h`, column `w`.
+    pub fn tile_origin(&self, h: usize, w: usize) -> (f32, f32) {
+        (
+            self.padding + w as f32 * (self.tile + self.gap),
+            self.padding + self.caption + h as f32 * (self.tile + self.gap) + self.drop(w),
+        )
+    }
+}
+
+/// The caption written above exported boards.
+pub fn caption(height: usize, width: usize, score: usize) -> String {
+    format!("SCORE {score}  {width}X{height}")
+}
+
+/// Writes `board` as an SVG or PNG picture, depending on the extension of `path`.
+pub fn write_image(
+    path: &Path,
+    board: &Board,
+    height: usize,
+    width: usize,
+    movement: Movement,
+    score: usize,
+) -> io::Result<()> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        fs::write(path, svg::render(board, height, width, movement, score))
+    } else {
+        let png = raster::render(board, height, width, movement, Some(score))
+            .encode_png()
+            .map_err(io::Error::other)?;
+        fs::write(path, png)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movement(grid: Grid) -> Movement {
+        Movement {
+            grid,
+            ..Movement::default()
+        }
+    }
+
+    #[test]
+    fn square_and_hex_image_sizes() {
+        let board = Board::from_contents(vec![Some(2); 12]);
+        // Four columns of 100 pixel tiles with 8 pixel gaps and 16 pixels of padding.
+        let sizes = [(Grid::Square, 456, 396), (Grid::Hex, 456, 558)];
+        for (grid, width, height) in sizes {
+            let svg = svg::render(&board, 3, 4, movement(grid), 0);
+            assert!(
+                svg.starts_with(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" ")),
+                "{grid:?}: {svg}"
+            );
+            let png = raster::render(&board, 3, 4, movement(grid), Some(0));
+            assert_eq!((png.width(), png.height()), (width, height), "{grid:?}");
+            let uncaptioned = raster::render(&board, 3, 4, movement(grid), None);
+            assert_eq!(uncaptioned.height(), height - 48, "{grid:?}");
+        }
+    }
+
+    #[test]
+    fn hex_columns_hang_half_a_cell_lower() {
+        let layout = Layout {
+            grid: Grid::Hex,
+            ..Layout::DEFAULT
+        };
+        assert_eq!(layout.tile_origin(0, 0), (16.0, 64.0));
+        assert_eq!(layout.tile_origin(0, 1), (124.0, 118.0));
+        assert_eq!(layout.tile_origin(1, 2), (232.0, 280.0));
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Draws boards into pixmaps on the CPU.
+use tiny_skia::{Color, LineCap, LineJoin, Paint, Path, PathBuilder, Pixmap, Stroke, Transform};
+
+use super::{caption, font, tile_colors, Layout, Rgb, BLOCKER, BOARD, EMPTY};
+use crate::engine::board::{pair_to_index, Board, Movement};
+
+fn paint(color: Rgb) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(Color::from_rgba8(color.0, color.1, color.2, 255));
+    paint.anti_alias = true;
+    paint
+}
+
+fn rounded_rect(x: f32, y: f32, width: f32, height: f32, radius: f32) -> Option<Path> {
+    let mut path = PathBuilder::new();
+    path.move_to(x + radius, y);
+    path.line_to(x + width - radius, y);
+    path.quad_to(x + width, y, x + width, y + radius);
+    path.line_to(x + width, y + height - radius);
+    path.quad_to(x + width, y + height, x + width - radius, y + height);
+    path.line_to(x + radius, y + height);
+    path.quad_to(x, y + height, x, y + height - radius);
+    path.line_to(x, y + radius);
+    path.quad_to(x, y, x + radius, y);
+    path.close();
+    path.finish()
+}
+
+/// Draws `text` with its centre at `(x, y)`.
+pub fn draw_text(pixmap: &mut Pixmap, text: &str, x: f32, y: f32, size: f32, color: Rgb) {
+    let left = x - font::text_width(text, size) / 2.0;
+    let top = y - size / 2.0;
+    let stroke = Stroke {
+        width: size / 7.0,
+        line_cap: LineCap::Round,
+        line_join: LineJoin::Round,
+        ..Stroke::default()
+    };
+    let paint = paint(color);
+    for line in font::strokes(text, left, top, size) {
+        let mut path = PathBuilder::new();
+        let Some((&(start_x, start_y), rest)) = line.split_first() else {
+            continue;
+        };
+        path.move_to(start_x, start_y);
+        rest.iter().for_each(|&(x, y)| path.line_to(x, y));
+        if let Some(path) = path.finish() {
+            pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+}
+
+/// Draws `board` with the default layout, with a score caption if one is given.
+pub fn render(
+    board: &Board,
+    height: usize,
+    width: usize,
+    movement: Movement,
+    score: Option<usize>,
+) -> Pixmap {
+    let layout = Layout {
+        caption: if score.is_some() {
+            Layout::DEFAULT.caption
+        } else {
+            0.0
+        },
+        grid: movement.grid,
+        ..Layout::DEFAULT
+    };
+    let (image_width, image_height) = layout.image_size(height, width);
+    let mut pixmap = Pixmap::new(image_width.ceil() as u32, image_height.ceil() as u32)
+        .expect("a board is never empty");
+    pixmap.fill(Color::from_rgba8(BOARD.0, BOARD.1, BOARD.2, 255));
+
+    if let Some(score) = score {
+        draw_text(
+            &mut pixmap,
+            &caption(height, width, score),
+            image_width / 2.0,
+            layout.padding + layout.caption / 2.0 - layout.gap / 2.0,
+            layout.caption / 2.0,
+            Rgb(255, 255, 255),
+        );
+    }
+
+    for h in 0..height {
+        for w in 0..width {
+            let (x, y) = layout.tile_origin(h, w);
+            let tile = board.0[pair_to_index(h, w, width)];
+            // The board shows through where it has no cell.
+            if tile.hole {
+                continue;
+            }
+            let content = tile.tilecontent;
+            let (background, text_color) = match content {
+                _ if tile.blocked => (BLOCKER, BLOCKER),
+                Some(content) => tile_colors(movement.merge.rule().rank(content)),
+                None => (EMPTY, EMPTY),
+            };
+            if let Some(path) = rounded_rect(x, y, layout.tile, layout.tile, layout.tile / 12.0) {
+                pixmap.fill_path(
+                    &path,
+                    &paint(background),
+                    tiny_skia::FillRule::Winding,
+                    Transform::identity(),
+                    None,
+                );
+            }
+            if let Some(content) = content {
+                let text = content.to_string();
+                // Shrink long numbers so they stay inside the tile.
+                let size = (layout.tile * 0.36)
+                    .min(layout.tile * 0.75 / font::text_width(&text, 1.0).max(f32::EPSILON));
+                draw_text(
+                    &mut pixmap,
+                    &text,
+                    x + layout.tile / 2.0,
+                    y + layout.tile / 2.0,
+                    size,
+                    text_color,
+                );
+            }
+        }
+    }
+    pixmap
+}
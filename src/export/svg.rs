@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Writes boards as SVG documents.
+use std::fmt::Write;
+
+use super::{caption, tile_colors, Layout, Rgb, BLOCKER, BOARD, EMPTY};
+use crate::engine::board::{pair_to_index, Board, Movement};
+
+fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+/// An SVG picture of `board` with a score caption.
+pub fn render(
+    board: &Board,
+    height: usize,
+    width: usize,
+    movement: Movement,
+    score: usize,
+) -> String {
+    let layout = Layout {
+        grid: movement.grid,
+        ..Layout::DEFAULT
+    };
+    let (image_width, image_height) = layout.image_size(height, width);
+    let mut svg = String::new();
+    // Writing to a `String` cannot fail.
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{image_width}\" height=\"{image_height}\" \
+         viewBox=\"0 0 {image_width} {image_height}\" font-family=\"sans-serif\" font-weight=\"bold\">"
+    );
+    let _ = writeln!(
+        svg,
+        "  <rect width=\"{image_width}\" height=\"{image_height}\" fill=\"{}\"/>",
+        hex(BOARD)
+    );
+    let _ = writeln!(
+        svg,
+        "  <text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#ffffff\" text-anchor=\"middle\" \
+         dominant-baseline=\"central\">{}</text>",
+        image_width / 2.0,
+        layout.padding + layout.caption / 2.0 - layout.gap / 2.0,
+        layout.caption / 2.0,
+        caption(height, width, score)
+    );
+    for h in 0..height {
+        for w in 0..width {
+            let (x, y) = layout.tile_origin(h, w);
+            let tile = board.0[pair_to_index(h, w, width)];
+            // The board shows through where it has no cell.
+            if tile.hole {
+                continue;
+            }
+            let content = tile.tilecontent;
+            let (background, text_color) = match content {
+                _ if tile.blocked => (BLOCKER, BLOCKER),
+                Some(content) => tile_colors(movement.merge.rule().rank(content)),
+                None => (EMPTY, EMPTY),
+            };
+            let _ = writeln!(
+                svg,
+                "  <rect x=\"{x}\" y=\"{y}\" width=\"{0}\" height=\"{0}\" rx=\"{1}\" fill=\"{2}\"/>",
+                layout.tile,
+                layout.tile / 12.0,
+                hex(background)
+            );
+            if let Some(content) = content {
+                let digits = content.to_string().len() as f32;
+                let size = (layout.tile * 0.45).min(layout.tile * 1.4 / digits);
+                let _ = writeln!(
+                    svg,
+                    "  <text x=\"{}\" y=\"{}\" font-size=\"{size}\" fill=\"{}\" \
+                     text-anchor=\"middle\" dominant-baseline=\"central\">{content}</text>",
+                    x + layout.tile / 2.0,
+                    y + layout.tile / 2.0,
+                    hex(text_color)
+                );
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,6 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The game engine and board drawing, shared by the COSMIC application and the other front-ends.
+pub mod engine;
+pub mod export;
+pub mod net;
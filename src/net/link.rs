@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: GPL-3.0-only
+use std::io;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use super::{Packet, VERSION};
+
+/// A connection to the other player, after both sides said hello.
+pub struct Link {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+}
+
+impl Link {
+    /// Waits for one player to join on `listener`.
+    pub async fn host(listener: &TcpListener) -> io::Result<Link> {
+        let (stream, _) = listener.accept().await?;
+        Link::handshake(stream).await
+    }
+
+    pub async fn join(address: impl ToSocketAddrs) -> io::Result<Link> {
+        Link::handshake(TcpStream::connect(address).await?).await
+    }
+
+    async fn handshake(stream: TcpStream) -> io::Result<Link> {
+        stream.set_nodelay(true)?;
+        let (reader, writer) = stream.into_split();
+        let mut link = Link {
+            lines: BufReader::new(reader).lines(),
+            writer,
+        };
+        link.send(&Packet::Hello { version: VERSION }).await?;
+        match link.receive().await? {
+            Some(Packet::Hello { version }) if version == VERSION => Ok(link),
+            Some(Packet::Hello { version }) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the other side speaks version {version} of the protocol, not {VERSION}"),
+            )),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the other side is not a 2048 game",
+            )),
+        }
+    }
+
+    pub async fn send(&mut self, packet: &Packet) -> io::Result<()> {
+        self.writer
+            .write_all(format!("{packet}\n").as_bytes())
+            .await
+    }
+
+    /// The next packet, or `None` once the other side hung up.
+    pub async fn receive(&mut self) -> io::Result<Option<Packet>> {
+        match self.lines.next_line().await? {
+            Some(line) => Packet::parse(&line)
+                .map(Some)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::board::Direction;
+    use crate::engine::spawn::SpawnRules;
+    use crate::net::Settings;
+
+    fn run<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn packets_round_trip() {
+        let packets = [
+            Packet::Hello { version: VERSION },
+            Packet::Game(Settings {
+                seed: 42,
+                width: 5,
+                height: 4,
+                seconds: 120,
+                spawn: SpawnRules::parse("2:1 4:1 each=2 evil=mean").unwrap(),
+            }),
+            Packet::Move(Direction::Down),
+            Packet::Done,
+            Packet::Bye,
+        ];
+        for packet in packets {
+            assert_eq!(Packet::parse(&packet.to_string()), Ok(packet));
+        }
+        assert!(Packet::parse("move X").is_err());
+        assert!(Packet::parse("game 1 4x4").is_err());
+        assert!(Packet::parse("game 1 0x4 120 2:9 4:1").is_err());
+        assert!(Packet::parse("game 1 4x100000 120 2:9 4:1").is_err());
+    }
+
+    #[test]
+    fn races_over_localhost() {
+        run(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+            let settings = Settings {
+                seed: 7,
+                width: 4,
+                height: 4,
+                seconds: 60,
+                spawn: SpawnRules::classic(),
+            };
+            let sent = settings.clone();
+            let host = tokio::spawn(async move {
+                let mut link = Link::host(&listener).await.unwrap();
+                link.send(&Packet::Game(sent)).await.unwrap();
+                link.send(&Packet::Move(Direction::Left)).await.unwrap();
+                link.send(&Packet::Done).await.unwrap();
+                link.receive().await.unwrap()
+            });
+            let mut link = Link::join(address).await.unwrap();
+            assert_eq!(link.receive().await.unwrap(), Some(Packet::Game(settings)));
+            assert_eq!(
+                link.receive().await.unwrap(),
+                Some(Packet::Move(Direction::Left))
+            );
+            assert_eq!(link.receive().await.unwrap(), Some(Packet::Done));
+            link.send(&Packet::Bye).await.unwrap();
+            assert_eq!(host.await.unwrap(), Some(Packet::Bye));
+            drop(link);
+        });
+    }
+
+    #[test]
+    fn refuses_other_versions() {
+        run(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+            let host = tokio::spawn(async move { Link::host(&listener).await.map(|_| ()) });
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            stream
+                .write_all(format!("hello {}\n", VERSION + 1).as_bytes())
+                .await
+                .unwrap();
+            let error = host.await.unwrap().unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        });
+    }
+}
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Races between two machines over TCP.
+//!
+//! Both players are dealt from the same seed and the spawns only depend on the
+//! moves, so streaming the directions is enough for each side to follow the
+//! other's board. Packets are lines of text:
+//!
+//! ```text
+//! hello 1
+//! game 12345 4x4 120 2:9 4:1
+//! move L
+//! done
+//! bye
+//! ```
+//!
+//! Both sides open with `hello` and the protocol version. The host then sends
+//! the `game` settings (seed, size, length of the race in seconds and the spawn
+//! rules) and the race is on. `done` tells the other side that no more moves
+//! follow, because the clock ran out or the board is stuck.
+pub mod link;
+
+use std::fmt;
+
+use crate::engine::board::Direction;
+use crate::engine::save::MAX_SIDE;
+use crate::engine::spawn::SpawnRules;
+
+/// Bumped whenever the packets change, players on different versions cannot race.
+pub const VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 20480;
+
+/// What both players need to be dealt the same game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub seed: u64,
+    pub width: usize,
+    pub height: usize,
+    pub seconds: u64,
+    pub spawn: SpawnRules,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Hello { version: u32 },
+    Game(Settings),
+    Move(Direction),
+    Done,
+    Bye,
+}
+
+impl Packet {
+    pub fn parse(line: &str) -> Result<Packet, String> {
+        let line = line.trim();
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let invalid = || format!("invalid {kind} packet \"{line}\"");
+        match kind {
+            "hello" => Ok(Packet::Hello {
+                version: rest.parse().map_err(|_| invalid())?,
+            }),
+            "game" => {
+                let mut words = rest.splitn(4, ' ');
+                let mut next = || words.next().ok_or_else(invalid);
+                let seed = next()?.parse().map_err(|_| invalid())?;
+                let (width, height) = next()?.split_once('x').ok_or_else(invalid)?;
+                let seconds = next()?.parse().map_err(|_| invalid())?;
+                let spawn = SpawnRules::parse(next()?)?;
+                let side = |side: &str| {
+                    side.parse()
+                        .ok()
+                        .filter(|side| (2..=MAX_SIDE).contains(side))
+                        .ok_or_else(|| format!("the board must be 2x2 to {MAX_SIDE}x{MAX_SIDE}"))
+                };
+                Ok(Packet::Game(Settings {
+                    seed,
+                    width: side(width)?,
+                    height: side(height)?,
+                    seconds,
+                    spawn,
+                }))
+            }
+            "move" => {
+                let mut letters = rest.chars();
+                match (
+                    letters.next().and_then(Direction::from_letter),
+                    letters.next(),
+                ) {
+                    (Some(direction), None) => Ok(Packet::Move(direction)),
+                    _ => Err(invalid()),
+                }
+            }
+            "done" => Ok(Packet::Done),
+            "bye" => Ok(Packet::Bye),
+            _ => Err(format!("unknown packet \"{line}\"")),
+        }
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Hello { version } => write!(f, "hello {version}"),
+            Packet::Game(settings) => write!(
+                f,
+                "game {} {}x{} {} {}",
+                settings.seed, settings.width, settings.height, settings.seconds, settings.spawn
+            ),
+            Packet::Move(direction) => write!(f, "move {}", direction.letter()),
+            Packet::Done => write!(f, "done"),
+            Packet::Bye => write!(f, "bye"),
+        }
+    }
+}